@@ -0,0 +1,60 @@
+use huobi_future_async as huobi_future;
+use crate::huobi_future::{decode::decode_event, models::WebsocketEvent};
+use failure::Fallible;
+use std::{env, fs, time::Instant};
+
+// Compares the topic-dispatched decoder with the untagged `WebsocketEvent` deserializer.
+//
+//   cargo run --release --example decode_bench [frames.txt]
+//
+// `frames.txt` holds one decompressed websocket frame per line, e.g. captured by logging
+// the `Incoming websocket message` trace. Without it `fixtures/ws_session.txt` is used:
+// 30 seconds of BTC_CQ / ETH_CQ market data, mostly depth updates and trades, like a
+// typical feed.
+fn main() -> Fallible<()> {
+    let text = match env::args().nth(1) {
+        Some(path) => fs::read_to_string(path)?,
+        None => include_str!("../fixtures/ws_session.txt").to_string(),
+    };
+    let frames: Vec<String> = text
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(str::to_string)
+        .collect();
+
+    // both decoders must agree on every frame before timing them
+    for frame in &frames {
+        let untagged = format!("{:?}", serde_json::from_str::<WebsocketEvent>(frame).ok());
+        let dispatched = format!("{:?}", decode_event(frame).ok());
+        if untagged != dispatched {
+            println!("decoders disagree on {}\n  untagged:   {}\n  dispatched: {}", frame, untagged, dispatched);
+        }
+    }
+
+    let rounds = 20_000;
+    let bytes: usize = frames.iter().map(String::len).sum::<usize>() * rounds;
+
+    let start = Instant::now();
+    for _ in 0..rounds {
+        for frame in &frames {
+            let _ = serde_json::from_str::<WebsocketEvent>(frame);
+        }
+    }
+    let untagged = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..rounds {
+        for frame in &frames {
+            let _ = decode_event(frame);
+        }
+    }
+    let dispatched = start.elapsed();
+
+    let count = (frames.len() * rounds) as f64;
+    println!("{} frames, {} bytes", frames.len() * rounds, bytes);
+    println!("untagged:   {:>10.0} ns/frame", untagged.as_nanos() as f64 / count);
+    println!("dispatched: {:>10.0} ns/frame", dispatched.as_nanos() as f64 / count);
+    println!("speedup:    {:>10.2}x", untagged.as_secs_f64() / dispatched.as_secs_f64());
+
+    Ok(())
+}
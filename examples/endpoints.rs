@@ -16,6 +16,11 @@ async fn main() -> Fallible<()> {
 
     let hb = HuobiFuture::with_credential(&access_key, &secret_key);
 
+    // sync signing clock with the server
+    if let Err(e) = hb.sync_time().await {
+        println!("Error: {}", e);
+    }
+
     // get contract info
     match hb.get_contract_info("BTC".to_string(), None, None)?.await {
         Ok(contract_info) => println!("{:?}", contract_info),
@@ -32,6 +37,36 @@ async fn main() -> Fallible<()> {
         Ok(account_position) => println!("{:?}", account_position),
         Err(e) => println!("Error: {}", e),
     }
+    // get available lever rates
+    match hb.get_available_level_rate("BTC".to_string())?.await {
+        Ok(level_rate) => println!("{:?}", level_rate),
+        Err(e) => println!("Error: {}", e),
+    }
+
+    // switch lever rate
+    match hb.switch_lever_rate("BTC", 5)?.await {
+        Ok(lever_rate) => println!("{:?}", lever_rate),
+        Err(e) => println!("Error: {}", e),
+    }
+
+    // get order limit
+    match hb.get_order_limit("BTC".to_string(), "limit")?.await {
+        Ok(order_limit) => println!("{:?}", order_limit),
+        Err(e) => println!("Error: {}", e),
+    }
+
+    // get trading fee
+    match hb.get_fee("BTC".to_string())?.await {
+        Ok(fee) => println!("{:?}", fee),
+        Err(e) => println!("Error: {}", e),
+    }
+
+    // get position limit
+    match hb.get_position_limit("BTC".to_string())?.await {
+        Ok(position_limit) => println!("{:?}", position_limit),
+        Err(e) => println!("Error: {}", e),
+    }
+
     // get orderbook
     match hb.get_all_book_tickers("BTC_CQ", "step6")?.await {
         Ok(orderbook) => println!("{:?}", orderbook),
@@ -66,8 +101,26 @@ async fn main() -> Fallible<()> {
         Err(e) => println!("Error: {}", e),
     }
 
+    // get open interest
+    match hb.get_open_interest("BTC".to_string(), "quarter".to_string(), None)?.await {
+        Ok(open_interest) => println!("{:?}", open_interest),
+        Err(e) => println!("Error: {}", e),
+    }
+
+    // get index price
+    match hb.get_index_price("BTC".to_string())?.await {
+        Ok(index_price) => println!("{:?}", index_price),
+        Err(e) => println!("Error: {}", e),
+    }
+
+    // get history trades
+    match hb.get_history_trades("BTC_CQ", 10)?.await {
+        Ok(history_trades) => println!("{:?}", history_trades),
+        Err(e) => println!("Error: {}", e),
+    }
+
     // place an order
-    match hb.place_order("BTC".to_string(), "quarter".to_string(), None, None, 12199.0, 1, "sell", "open", 1, "limit")?.await {
+    match hb.place_order("BTC".to_string(), "quarter".to_string(), None, None, 12199.0, 1, "sell", "open", 1, "limit", None)?.await {
         Ok(order) => println!("{:?}", order),
         Err(e) => println!("Error: {}", e),
     }
@@ -79,13 +132,14 @@ async fn main() -> Fallible<()> {
                     contract_code: None,
                     symbol: Some("BTC".to_string()),
                     contract_type: Some("quarter".to_string()),
-                    client_order_id: Some(123),
+                    client_order_id: Some(ClientOrderId(123)),
                     price: Some(11999.1),
                     volume: 1,
                     direction: "sell".to_string(),
                     offset: "open".to_string(),
                     lever_rate: 1,
                     order_price_type: "limit".to_string(),
+                    tpsl: None,
                 }
             ]
         };
@@ -96,7 +150,7 @@ async fn main() -> Fallible<()> {
     }
 
     // cancel orders
-    match hb.cancel_orders("BTC".to_string(), None, "123".to_string())?.await {
+    match hb.cancel_orders("BTC".to_string(), None, &[ClientOrderId(123)])?.await {
         Ok(cancelorders) => println!("{:?}", cancelorders),
         Err(e) => println!("{:?}", e),
     }
@@ -108,7 +162,7 @@ async fn main() -> Fallible<()> {
     }
 
     // get order info
-    match hb.get_order_info("BTC".to_string(), None, "123".to_string())?.await {
+    match hb.get_order_info("BTC".to_string(), None, &[ClientOrderId(123)])?.await {
         Ok(order_info) => println!("{:?}", order_info),
         Err(e) => println!("{:?}", e),
     }
@@ -163,6 +217,49 @@ async fn main() -> Fallible<()> {
         Err(e) => println!("{:?}", e),
     }
 
+    // place tpsl order
+    match hb.place_tpsl_order("BTC".to_string(), "quarter".to_string(), None, "sell", 1,
+                              13000.0, None, "optimal_5".to_string(), 11000.0, None, "optimal_5".to_string())?.await {
+        Ok(tpsl_order) => println!("{:?}", tpsl_order),
+        Err(e) => println!("{:?}", e),
+    }
+
+    // get tpsl open orders
+    match hb.get_tpsl_open_orders("BTC".to_string(), None, None, None, None)?.await {
+        Ok(tpsl_open_orders) => println!("{:?}", tpsl_open_orders),
+        Err(e) => println!("{:?}", e),
+    }
+
+    // get tpsl his orders
+    match hb.get_tpsl_his_orders("BTC".to_string(), None, "0".to_string(), 1, None, None, None)?.await {
+        Ok(tpsl_his_orders) => println!("{:?}", tpsl_his_orders),
+        Err(e) => println!("{:?}", e),
+    }
+
+    // cancel all tpsl orders
+    match hb.cancel_all_tpsl_orders("BTC".to_string(), None, None, None)?.await {
+        Ok(cancel_all_tpsl_orders) => println!("{:?}", cancel_all_tpsl_orders),
+        Err(e) => println!("{:?}", e),
+    }
+
+    // place track order
+    match hb.place_track_order("BTC".to_string(), "quarter".to_string(), None, "sell", "open", 1, 1, 0.01, 12500.0, "optimal_5")?.await {
+        Ok(track_order) => println!("{:?}", track_order),
+        Err(e) => println!("{:?}", e),
+    }
+
+    // get track open orders
+    match hb.get_track_open_orders("BTC".to_string(), None, None, None, None)?.await {
+        Ok(track_open_orders) => println!("{:?}", track_open_orders),
+        Err(e) => println!("{:?}", e),
+    }
+
+    // cancel all track orders
+    match hb.cancel_all_track_orders("BTC".to_string(), None, None, None, None)?.await {
+        Ok(cancel_all_track_orders) => println!("{:?}", cancel_all_track_orders),
+        Err(e) => println!("{:?}", e),
+    }
+
     // transfer between spot and future
     match hb.transfer("BTC".to_string(), 0.001, "futures-to-pro")?.await {
         Ok(transfer) => println!("{:?}", transfer),
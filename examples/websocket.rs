@@ -1,4 +1,4 @@
-use crate::huobi_future::{models::Subscription, models::WebsocketEvent, HuobiWebsocket};
+use crate::huobi_future::{models::Subscription, models::WebsocketEvent, HuobiFuture, HuobiWebsocket};
 use huobi_future_async as huobi_future;
 use failure::Fallible;
 use std::{
@@ -13,7 +13,11 @@ async fn main() -> Fallible<()> {
     let access_key = "";
     let secret_key = "";
 
-    let mut ws: HuobiWebsocket = HuobiWebsocket::new(access_key, secret_key, |event: WebsocketEvent| {
+    // sign the websocket auth with the exchange clock
+    let hb = HuobiFuture::with_credential(access_key, secret_key);
+    hb.sync_time().await?;
+
+    let mut ws: HuobiWebsocket = HuobiWebsocket::from_client(&hb, |event: WebsocketEvent| {
         match event {
             
             WebsocketEvent::OrderBook(orderbook) => println!("orderbook:{:?}", orderbook), 
@@ -29,9 +33,11 @@ async fn main() -> Fallible<()> {
             WebsocketEvent::ContractInfo(contract_info) => println!("contract_info:{:?}", contract_info),
             WebsocketEvent::Liquidation(liquidation_orders) => println!("liquidation orders:{:?}", liquidation_orders),
             WebsocketEvent::TriggerOrder(order) => println!("Trigger order:{:?}", order),
+            WebsocketEvent::TrackOrder(order) => println!("Track order:{:?}", order),
 
             WebsocketEvent::Index(index_price) => println!("index_price:{:?}", index_price),
             WebsocketEvent::Basis(basis) => println!("basis:{:?}",basis),
+            WebsocketEvent::ServiceHeartbeat(heartbeat) => println!("heartbeat:{:?}", heartbeat),
 
 
             _ => (),
@@ -71,6 +77,12 @@ async fn main() -> Fallible<()> {
 
     subs.insert(Subscription::Index, index_topics);
 
+    let notification_topics = vec![
+        "public.futures.heartbeat",
+    ];
+
+    subs.insert(Subscription::CenterNotification, notification_topics);
+
     if let Err(e) = ws.connect(subs).await {
         println!("### websocket error: {:?}", e);
     }
@@ -6,6 +6,11 @@ use failure::Fallible;
 use futures::prelude::*;
 use std::{collections::BTreeMap};
 
+// Max orders per contract_batchorder request.
+pub const BATCH_ORDER_MAX: usize = 10;
+// Max ids per contract_cancel / contract_trigger_cancel request.
+pub const CANCEL_ORDER_MAX: usize = 10;
+
 impl HuobiFuture {
     // Account Information
     pub fn get_account_info<S1>(
@@ -45,8 +50,103 @@ impl HuobiFuture {
         )
     }
 
+    // switch lever rate
+    pub fn switch_lever_rate<S1>(
+        &self,
+        symbol: S1,
+        lever_rate: u32,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<LeverRate>>>>
+    where
+        S1: Into<String>
+    {
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        params.insert("symbol".into(), symbol.into());
+        params.insert("lever_rate".into(), format!("{}", lever_rate));
+
+        Ok(self
+            .transport
+            .signed_post("/api/v1/contract_switch_lever_rate", Some(params))?
+        )
+    }
+
+    // get available lever rates
+    pub fn get_available_level_rate<S1>(
+        &self,
+        symbol: S1,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Vec<AvailableLevelRate>>>>>
+    where
+        S1: Into<Option<String>>
+    {
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        if let Some(sym) = symbol.into() { params.insert("symbol".into(), sym); }
+
+        Ok(self
+            .transport
+            .signed_post("/api/v1/contract_available_level_rate", Some(params))?
+        )
+    }
+
+    // get order limit
+    pub fn get_order_limit<S1, S2>(
+        &self,
+        symbol: S1,
+        order_price_type: S2,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<OrderLimit>>>>
+    where
+        S1: Into<Option<String>>,
+        S2: Into<String>
+    {
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        if let Some(sym) = symbol.into() { params.insert("symbol".into(), sym); }
+        params.insert("order_price_type".into(), order_price_type.into());
+
+        Ok(self
+            .transport
+            .signed_post("/api/v1/contract_order_limit", Some(params))?
+        )
+    }
+
+    // get trading fee
+    pub fn get_fee<S1>(
+        &self,
+        symbol: S1,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Vec<ContractFee>>>>>
+    where
+        S1: Into<Option<String>>
+    {
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        if let Some(sym) = symbol.into() { params.insert("symbol".into(), sym); }
+
+        Ok(self
+            .transport
+            .signed_post("/api/v1/contract_fee", Some(params))?
+        )
+    }
+
+    // get position limit
+    pub fn get_position_limit<S1>(
+        &self,
+        symbol: S1,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Vec<PositionLimit>>>>>
+    where
+        S1: Into<Option<String>>
+    {
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        if let Some(sym) = symbol.into() { params.insert("symbol".into(), sym); }
+
+        Ok(self
+            .transport
+            .signed_post("/api/v1/contract_position_limit", Some(params))?
+        )
+    }
+
     // place an order
-    pub fn place_order<S1, S2, S3, S4, S5, S6, S7, S8>(
+    pub fn place_order<S1, S2, S3, S4, S5, S6, S7, S8, S9>(
         &self, 
         symbol: S1, 
         contract_type: S2, 
@@ -57,50 +157,165 @@ impl HuobiFuture {
         direction: S6, 
         offset: S7, 
         lever_rate: u32, 
-        order_price_type: S8
+        order_price_type: S8,
+        tpsl: S9
     ) -> Fallible<impl Future<Output = Fallible<APIResponse<OrderId>>>>
     where 
         S1: Into<Option<String>>, 
         S2: Into<Option<String>>, 
         S3: Into<Option<String>>, 
-        S4: Into<Option<u32>>,
+        S4: Into<Option<ClientOrderId>>,
         S5: Into<Option<f64>>, 
         S6: Into<String>, 
         S7: Into<String>, 
-        S8: Into<String>
+        S8: Into<String>,
+        S9: Into<Option<OrderTpsl>>
     {
-        let mut params: BTreeMap<String, String> = BTreeMap::new();
+        self.check_maintenance()?;
+
+        let mut order = OrderRequest {
+            contract_code: contract_code.into().map(ContractCode::from),
+            symbol: symbol.into(),
+            contract_type: contract_type.into(),
+            client_order_id: Some(client_order_id.into().unwrap_or_else(|| self.next_client_order_id())),
+            price: price.into(),
+            volume,
+            direction: direction.into(),
+            offset: offset.into(),
+            lever_rate,
+            order_price_type: order_price_type.into(),
+            tpsl: tpsl.into(),
+        };
+        self.check_order_settlement(&order)?;
+        self.check_pre_trade(std::slice::from_mut(&mut order))?;
+        // released again if the request can't be built or never completes
+        let pending = self.pending_orders(vec![order.client_order_id]);
 
-        params.insert("volume".into(), format!("{}", volume));
-        params.insert("direction".into(), direction.into());
-        params.insert("offset".into(), offset.into());
-        params.insert("lever_rate".into(), lever_rate.to_string());
-        params.insert("order_price_type".into(), order_price_type.into());
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
 
-        if let Some(client_id) = client_order_id.into() { params.insert("client_order_id".into(), format!("{}", client_id)); }
-        if let Some(p) = price.into() { params.insert("price".into(), format!("{}", p)); }
-        if let Some(code) = contract_code.into() { params.insert("contract_code".into(), code); }
-        if let Some(ctype) = contract_type.into() { params.insert("contract_type".into(), ctype); }
-        if let Some(sym) = symbol.into() { params.insert("symbol".into(), sym); }
+        params.insert("volume".into(), format!("{}", order.volume));
+        params.insert("direction".into(), order.direction);
+        params.insert("offset".into(), order.offset);
+        params.insert("lever_rate".into(), order.lever_rate.to_string());
+        params.insert("order_price_type".into(), order.order_price_type);
+
+        if let Some(client_id) = order.client_order_id { params.insert("client_order_id".into(), format!("{}", client_id)); }
+        if let Some(p) = order.price { params.insert("price".into(), format!("{}", p)); }
+        if let Some(code) = order.contract_code { params.insert("contract_code".into(), code.into()); }
+        if let Some(ctype) = order.contract_type { params.insert("contract_type".into(), ctype); }
+        if let Some(sym) = order.symbol { params.insert("symbol".into(), sym); }
+
+        if let Some(tpsl) = order.tpsl {
+            if let Some(p) = tpsl.tp_trigger_price { params.insert("tp_trigger_price".into(), format!("{}", p)); }
+            if let Some(p) = tpsl.tp_order_price { params.insert("tp_order_price".into(), format!("{}", p)); }
+            if let Some(ptype) = tpsl.tp_order_price_type { params.insert("tp_order_price_type".into(), ptype); }
+            if let Some(p) = tpsl.sl_trigger_price { params.insert("sl_trigger_price".into(), format!("{}", p)); }
+            if let Some(p) = tpsl.sl_order_price { params.insert("sl_order_price".into(), format!("{}", p)); }
+            if let Some(ptype) = tpsl.sl_order_price_type { params.insert("sl_order_price_type".into(), ptype); }
+        }
 
-        Ok(self
+        let fut = self
             .transport
-            .signed_post("/api/v1/contract_order", Some(params))?)    
+            .signed_post("/api/v1/contract_order", Some(params))?;
+
+        Ok(pending.settle(fut, |_| Vec::new()))
 
     }
 
     // place batch order
     pub fn place_orders(
         &self, 
-        orders_data: BatchOrderRequest
+        mut orders_data: BatchOrderRequest
     ) -> Fallible<impl Future<Output = Fallible<APIResponse<BatchOrder>>>>
     {
-        Ok(self
+        self.check_maintenance()?;
+        for order in &mut orders_data.orders_data {
+            self.check_order_settlement(order)?;
+            order.client_order_id.get_or_insert_with(|| self.next_client_order_id());
+        }
+        self.check_pre_trade(&mut orders_data.orders_data)?;
+        let pending = self.pending_orders(orders_data.orders_data.iter().map(|o| o.client_order_id).collect());
+
+        let fut = self
             .transport
-            .signed_post("/api/v1/contract_batchorder", Some(orders_data))?)    
+            .signed_post("/api/v1/contract_batchorder", Some(orders_data))?;
+
+        // batch indexes are 1-based
+        Ok(pending.settle(fut, |resp: &APIResponse<BatchOrder>| {
+            resp.data
+                .iter()
+                .flat_map(|batch| &batch.errors)
+                .map(|e| (e.index as usize).wrapping_sub(1))
+                .collect()
+        }))
     } 
 
 
+    // place any number of orders, split into batches of at most BATCH_ORDER_MAX
+    pub async fn place_orders_chunked(
+        &self,
+        mut orders: Vec<OrderRequest>
+    ) -> Fallible<Vec<BatchOrderOutcome>>
+    {
+        self.check_maintenance()?;
+        for order in &orders {
+            self.check_order_settlement(order)?;
+        }
+
+        // assign ids up front so every outcome can be correlated and retried
+        for order in &mut orders {
+            order.client_order_id.get_or_insert_with(|| self.next_client_order_id());
+        }
+
+        let mut outcomes = Vec::with_capacity(orders.len());
+        for chunk in orders.chunks(BATCH_ORDER_MAX) {
+            self.acquire_rate().await;
+
+            let request = BatchOrderRequest { orders_data: chunk.to_vec() };
+            let result = match self.place_orders(request) {
+                Ok(fut) => fut.await,
+                Err(e) => Err(e),
+            };
+
+            let mut results: Vec<Option<BatchOrderResult>> = vec![None; chunk.len()];
+            match result {
+                Ok(resp) => {
+                    let batch = resp.data.unwrap_or(BatchOrder { errors: Vec::new(), success: Vec::new() });
+                    // batch indexes are 1-based
+                    for s in batch.success {
+                        if let Some(slot) = results.get_mut((s.index as usize).wrapping_sub(1)) {
+                            *slot = Some(BatchOrderResult::Placed(OrderId {
+                                order_id: s.order_id,
+                                order_id_str: s.order_id_str,
+                                client_order_id: s.client_order_id,
+                            }));
+                        }
+                    }
+                    for e in batch.errors {
+                        if let Some(slot) = results.get_mut((e.index as usize).wrapping_sub(1)) {
+                            *slot = Some(BatchOrderResult::Rejected(e));
+                        }
+                    }
+                }
+                Err(e) => {
+                    let reason = e.to_string();
+                    for slot in &mut results {
+                        *slot = Some(BatchOrderResult::Failed(reason.clone()));
+                    }
+                }
+            }
+
+            for (request, result) in chunk.iter().zip(results) {
+                outcomes.push(BatchOrderOutcome {
+                    request: request.clone(),
+                    result: result.unwrap_or_else(|| BatchOrderResult::Failed("missing from batch response".into())),
+                });
+            }
+        }
+
+        Ok(outcomes)
+    }
+
     // lightning close
     pub fn lightning_close<S1, S2, S3, S4, S5, S6>(
         &self,
@@ -117,18 +332,24 @@ impl HuobiFuture {
         S2: Into<Option<String>>,
         S3: Into<Option<String>>,
         S4: Into<String>,
-        S5: Into<Option<u64>>,
+        S5: Into<Option<ClientOrderId>>,
         S6: Into<Option<String>>
     {
+        self.check_maintenance()?;
+        let symbol = symbol.into();
+        let contract_code = contract_code.into();
+        self.check_settlement(symbol.as_deref(), contract_code.as_deref())?;
+
         let mut params: BTreeMap<String, String> = BTreeMap::new();
 
         params.insert("volume".into(), format!("{}", volume));
         params.insert("direction".into(), format!("{}", direction.into()));
-        if let Some(code) = contract_code.into() { params.insert("contract_code".into(), code); }
+        if let Some(code) = contract_code { params.insert("contract_code".into(), code); }
         if let Some(ctype) = contract_type.into() { params.insert("contract_type".into(), ctype); }
-        if let Some(sym) = symbol.into() { params.insert("symbol".into(), sym); }
+        if let Some(sym) = symbol { params.insert("symbol".into(), sym); }
         if let Some(otype) = order_price_type.into() { params.insert("order_price_type".into(), otype); }
-        if let Some(client_id) = client_order_id.into() { params.insert("client_order_id".into(), format!("{}", client_id)); }
+        let client_id = client_order_id.into().unwrap_or_else(|| self.next_client_order_id());
+        params.insert("client_order_id".into(), format!("{}", client_id));
 
         Ok(self
             .transport
@@ -138,20 +359,19 @@ impl HuobiFuture {
     }
 
     // cancel orders
-    pub fn cancel_orders<S1, S2>(
+    pub fn cancel_orders<S1>(
         &self,
         symbol: String,
         order_id: S1,
-        client_order_id: S2,
+        client_order_ids: &[ClientOrderId],
     ) -> Fallible<impl Future<Output = Fallible<APIResponse<Cancel>>>>
     where
         S1: Into<Option<String>>,
-        S2: Into<Option<String>>
     {   
         let mut params: BTreeMap<String, String> = BTreeMap::new();
         
         if let Some(oid) = order_id.into() { params.insert("order_id".into(), format!("{}", oid));}
-        if let Some(cid) = client_order_id.into() { params.insert("client_order_id".into(), format!("{}", cid));}
+        if !client_order_ids.is_empty() { params.insert("client_order_id".into(), join_ids(client_order_ids));}
 
         params.insert("symbol".into(), symbol);
 
@@ -161,6 +381,67 @@ impl HuobiFuture {
         )
     }
 
+    // cancel any number of orders by order id, in chunks of CANCEL_ORDER_MAX
+    pub async fn cancel_order_ids<T: ToString>(
+        &self,
+        symbol: &str,
+        order_ids: &[T]
+    ) -> Fallible<Vec<CancelOutcome>>
+    {
+        Ok(self.cancel_in_chunks(order_ids, |chunk| self.cancel_orders(symbol.to_string(), join_ids(chunk), &[])).await)
+    }
+
+    // cancel any number of orders by client order id, in chunks of CANCEL_ORDER_MAX
+    pub async fn cancel_client_order_ids(
+        &self,
+        symbol: &str,
+        client_order_ids: &[ClientOrderId]
+    ) -> Fallible<Vec<CancelOutcome>>
+    {
+        Ok(self.cancel_in_chunks(client_order_ids, |chunk| self.cancel_orders(symbol.to_string(), None, chunk)).await)
+    }
+
+    async fn cancel_in_chunks<T, F, Fut>(&self, ids: &[T], send: F) -> Vec<CancelOutcome>
+    where
+        T: ToString,
+        F: Fn(&[T]) -> Fallible<Fut>,
+        Fut: Future<Output = Fallible<APIResponse<Cancel>>>
+    {
+        let mut outcomes = Vec::with_capacity(ids.len());
+        for chunk in ids.chunks(CANCEL_ORDER_MAX) {
+            self.acquire_rate().await;
+
+            let result = match send(chunk) {
+                Ok(fut) => fut.await,
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(resp) => {
+                    let cancel = resp.data.unwrap_or(Cancel { errors: Vec::new(), successes: String::new() });
+                    for id in chunk.iter().map(ToString::to_string) {
+                        let result = if cancel.successes.split(',').any(|s| s.trim() == id) {
+                            CancelResult::Cancelled
+                        } else if let Some(e) = cancel.errors.iter().find(|e| e.order_id == id) {
+                            CancelResult::Rejected(e.clone())
+                        } else {
+                            CancelResult::Failed("missing from cancel response".into())
+                        };
+                        outcomes.push(CancelOutcome { id, result });
+                    }
+                }
+                Err(e) => {
+                    let reason = e.to_string();
+                    for id in chunk {
+                        outcomes.push(CancelOutcome { id: id.to_string(), result: CancelResult::Failed(reason.clone()) });
+                    }
+                }
+            }
+        }
+
+        outcomes
+    }
+
     // cancel all orders
     pub fn cancel_allorders<S1, S2>(
         &self,
@@ -186,23 +467,22 @@ impl HuobiFuture {
     }
 
     // get order info
-    pub fn get_order_info<S1, S2, S3>(
+    pub fn get_order_info<S1, S2>(
         &self,
         symbol: S1,
         order_id: S2,
-        client_order_id: S3,
+        client_order_ids: &[ClientOrderId],
     )-> Fallible<impl Future<Output = Fallible<APIResponse<Vec<Order>>>>>
     where
         S1: Into<String>,
         S2: Into<Option<String>>,
-        S3: Into<Option<String>>
     {
         let mut params: BTreeMap<String, String> = BTreeMap::new();
 
         params.insert("symbol".into(), symbol.into());
 
         if let Some(oid) = order_id.into() { params.insert("order_id".into(), oid);}
-        if let Some(cid) = client_order_id.into() { params.insert("client_order_id".into(), cid);}
+        if !client_order_ids.is_empty() { params.insert("client_order_id".into(), join_ids(client_order_ids));}
 
         Ok(self
             .transport
@@ -294,11 +574,16 @@ impl HuobiFuture {
         S6: Into<String>,
         S7: Into<String>
     {
+        self.check_maintenance()?;
+        let symbol = symbol.into();
+        let contract_code = contract_code.into();
+        self.check_settlement(symbol.as_deref(), contract_code.as_deref())?;
+
         let mut params: BTreeMap<String, String> = BTreeMap::new();
 
-        if let Some(sym) = symbol.into() { params.insert("symbol".into(), sym); }
+        if let Some(sym) = symbol { params.insert("symbol".into(), sym); }
         if let Some(ctype) = contract_type.into() { params.insert("contract_type".into(), ctype); }
-        if let Some(code) = contract_code.into() { params.insert("contract_code".into(), code); }
+        if let Some(code) = contract_code { params.insert("contract_code".into(), code); }
         if let Some(otype) = order_price_type.into() { params.insert("order_price_type".into(), otype); }
 
         params.insert("trigger_type".into(), trigger_type.into());
@@ -337,6 +622,16 @@ impl HuobiFuture {
         )
     }
 
+    // cancel any number of trigger orders, in chunks of CANCEL_ORDER_MAX
+    pub async fn cancel_trigger_order_ids<T: ToString>(
+        &self,
+        symbol: &str,
+        order_ids: &[T]
+    ) -> Fallible<Vec<CancelOutcome>>
+    {
+        Ok(self.cancel_in_chunks(order_ids, |chunk| self.cancel_trigger_orders(symbol, join_ids(chunk))).await)
+    }
+
     // cancel all trigger orders
     pub fn cancel_all_trigger_orders<S1, S2, S3>(
         &self,
@@ -426,6 +721,379 @@ impl HuobiFuture {
 
     }
 
+    // countdown cancel all orders, time_out in milliseconds
+    pub fn cancel_after<S1>(
+        &self,
+        on_off: bool,
+        time_out: S1,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<CancelAfter>>>>
+    where
+        S1: Into<Option<u64>>
+    {
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        params.insert("on_off".into(), if on_off { "1".into() } else { "0".into() });
+        if let Some(t) = time_out.into() { params.insert("time_out".into(), format!("{}", t)); }
+
+        Ok(self
+            .transport
+            .signed_post("/api/v1/contract-cancel-after", Some(params))?
+        )
+    }
+
+    // place take-profit / stop-loss order
+    pub fn place_tpsl_order<S1, S2, S3, S4, S5, S6, S7, S8, S9, S10>(
+        &self,
+        symbol: S1,
+        contract_type: S2,
+        contract_code: S3,
+        direction: S4,
+        volume: u32,
+        tp_trigger_price: S5,
+        tp_order_price: S6,
+        tp_order_price_type: S7,
+        sl_trigger_price: S8,
+        sl_order_price: S9,
+        sl_order_price_type: S10,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<TpslOrder>>>>
+    where
+        S1: Into<String>,
+        S2: Into<Option<String>>,
+        S3: Into<Option<String>>,
+        S4: Into<String>,
+        S5: Into<Option<f64>>,
+        S6: Into<Option<f64>>,
+        S7: Into<Option<String>>,
+        S8: Into<Option<f64>>,
+        S9: Into<Option<f64>>,
+        S10: Into<Option<String>>
+    {
+        self.check_maintenance()?;
+        let symbol = symbol.into();
+        let contract_code = contract_code.into();
+        self.check_settlement(Some(&symbol), contract_code.as_deref())?;
+
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        params.insert("symbol".into(), symbol);
+        params.insert("direction".into(), direction.into());
+        params.insert("volume".into(), format!("{}", volume));
+
+        if let Some(ctype) = contract_type.into() { params.insert("contract_type".into(), ctype); }
+        if let Some(code) = contract_code { params.insert("contract_code".into(), code); }
+        if let Some(p) = tp_trigger_price.into() { params.insert("tp_trigger_price".into(), format!("{}", p)); }
+        if let Some(p) = tp_order_price.into() { params.insert("tp_order_price".into(), format!("{}", p)); }
+        if let Some(ptype) = tp_order_price_type.into() { params.insert("tp_order_price_type".into(), ptype); }
+        if let Some(p) = sl_trigger_price.into() { params.insert("sl_trigger_price".into(), format!("{}", p)); }
+        if let Some(p) = sl_order_price.into() { params.insert("sl_order_price".into(), format!("{}", p)); }
+        if let Some(ptype) = sl_order_price_type.into() { params.insert("sl_order_price_type".into(), ptype); }
+
+        Ok(self
+            .transport
+            .signed_post("/api/v1/contract_tpsl_order", Some(params))?
+        )
+    }
+
+    // cancel take-profit / stop-loss orders
+    pub fn cancel_tpsl_orders<S1, S2>(
+        &self,
+        symbol: S1,
+        order_id: S2,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Cancel>>>>
+    where
+        S1: Into<String>,
+        S2: Into<String>
+    {
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        params.insert("symbol".into(), symbol.into());
+        params.insert("order_id".into(), order_id.into());
+
+        Ok(self
+            .transport
+            .signed_post("/api/v1/contract_tpsl_cancel", Some(params))?
+        )
+    }
+
+    // cancel all take-profit / stop-loss orders
+    pub fn cancel_all_tpsl_orders<S1, S2, S3, S4>(
+        &self,
+        symbol: S1,
+        contract_code: S2,
+        contract_type: S3,
+        direction: S4,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Cancel>>>>
+    where
+        S1: Into<String>,
+        S2: Into<Option<String>>,
+        S3: Into<Option<String>>,
+        S4: Into<Option<String>>
+    {
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        params.insert("symbol".into(), symbol.into());
+        if let Some(code) = contract_code.into() { params.insert("contract_code".into(), code); }
+        if let Some(ctype) = contract_type.into() { params.insert("contract_type".into(), ctype); }
+        if let Some(dir) = direction.into() { params.insert("direction".into(), dir); }
+
+        Ok(self
+            .transport
+            .signed_post("/api/v1/contract_tpsl_cancelall", Some(params))?
+        )
+    }
+
+    // query take-profit / stop-loss open orders
+    pub fn get_tpsl_open_orders<S1, S2, S3, S4, S5>(
+        &self,
+        symbol: S1,
+        contract_code: S2,
+        trade_type: S3,
+        page_index: S4,
+        page_size: S5,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Page<TpslOpenOrder>>>>>
+    where
+        S1: Into<String>,
+        S2: Into<Option<String>>,
+        S3: Into<Option<u32>>,
+        S4: Into<Option<u32>>,
+        S5: Into<Option<u32>>
+    {
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        params.insert("symbol".into(), symbol.into());
+        if let Some(code) = contract_code.into() { params.insert("contract_code".into(), code); }
+        if let Some(ttype) = trade_type.into() { params.insert("trade_type".into(), format!("{}", ttype)); }
+        if let Some(index) = page_index.into() { params.insert("page_index".into(), format!("{}", index)); }
+        if let Some(size) = page_size.into() { params.insert("page_size".into(), format!("{}", size)); }
+
+        Ok(self
+            .transport
+            .signed_post("/api/v1/contract_tpsl_openorders", Some(params))?
+        )
+    }
+
+    // query take-profit / stop-loss history orders
+    pub fn get_tpsl_his_orders<S1, S2, S3, S4, S5, S6>(
+        &self,
+        symbol: S1,
+        contract_code: S2,
+        status: S3,
+        create_date: u32,
+        page_index: S4,
+        page_size: S5,
+        sort_by: S6,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Page<TpslHisOrder>>>>>
+    where
+        S1: Into<String>,
+        S2: Into<Option<String>>,
+        S3: Into<String>,
+        S4: Into<Option<u32>>,
+        S5: Into<Option<u32>>,
+        S6: Into<Option<String>>
+    {
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        params.insert("symbol".into(), symbol.into());
+        params.insert("status".into(), status.into());
+        params.insert("create_date".into(), format!("{}", create_date));
+
+        if let Some(code) = contract_code.into() { params.insert("contract_code".into(), code); }
+        if let Some(index) = page_index.into() { params.insert("page_index".into(), format!("{}", index)); }
+        if let Some(size) = page_size.into() { params.insert("page_size".into(), format!("{}", size)); }
+        if let Some(sort) = sort_by.into() { params.insert("sort_by".into(), sort); }
+
+        Ok(self
+            .transport
+            .signed_post("/api/v1/contract_tpsl_hisorders", Some(params))?
+        )
+    }
+
+    // query an order together with its take-profit / stop-loss orders
+    pub fn get_relation_tpsl_order<S1>(
+        &self,
+        symbol: S1,
+        order_id: u64,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<RelationTpslOrder>>>>
+    where
+        S1: Into<String>
+    {
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        params.insert("symbol".into(), symbol.into());
+        params.insert("order_id".into(), format!("{}", order_id));
+
+        Ok(self
+            .transport
+            .signed_post("/api/v1/contract_relation_tpsl_order", Some(params))?
+        )
+    }
+
+    // place track order
+    pub fn place_track_order<S1, S2, S3, S4, S5, S6>(
+        &self,
+        symbol: S1,
+        contract_type: S2,
+        contract_code: S3,
+        direction: S4,
+        offset: S5,
+        lever_rate: u32,
+        volume: u32,
+        callback_rate: f64,
+        active_price: f64,
+        order_price_type: S6,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<OrderId>>>>
+    where
+        S1: Into<String>,
+        S2: Into<Option<String>>,
+        S3: Into<Option<String>>,
+        S4: Into<String>,
+        S5: Into<String>,
+        S6: Into<String>
+    {
+        self.check_maintenance()?;
+        let symbol = symbol.into();
+        let contract_code = contract_code.into();
+        self.check_settlement(Some(&symbol), contract_code.as_deref())?;
+
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        params.insert("symbol".into(), symbol);
+        if let Some(ctype) = contract_type.into() { params.insert("contract_type".into(), ctype); }
+        if let Some(code) = contract_code { params.insert("contract_code".into(), code); }
+
+        params.insert("direction".into(), direction.into());
+        params.insert("offset".into(), offset.into());
+        params.insert("lever_rate".into(), format!("{}", lever_rate));
+        params.insert("volume".into(), format!("{}", volume));
+        params.insert("callback_rate".into(), format!("{}", callback_rate));
+        params.insert("active_price".into(), format!("{}", active_price));
+        params.insert("order_price_type".into(), order_price_type.into());
+
+        Ok(self
+            .transport
+            .signed_post("/api/v1/contract_track_order", Some(params))?
+        )
+    }
+
+    // cancel track orders
+    pub fn cancel_track_orders<S1, S2>(
+        &self,
+        symbol: S1,
+        order_id: S2,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Cancel>>>>
+    where
+        S1: Into<String>,
+        S2: Into<String>
+    {
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        params.insert("symbol".into(), symbol.into());
+        params.insert("order_id".into(), order_id.into());
+
+        Ok(self
+            .transport
+            .signed_post("/api/v1/contract_track_cancel", Some(params))?
+        )
+    }
+
+    // cancel all track orders
+    pub fn cancel_all_track_orders<S1, S2, S3, S4, S5>(
+        &self,
+        symbol: S1,
+        contract_code: S2,
+        contract_type: S3,
+        direction: S4,
+        offset: S5,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Cancel>>>>
+    where
+        S1: Into<String>,
+        S2: Into<Option<String>>,
+        S3: Into<Option<String>>,
+        S4: Into<Option<String>>,
+        S5: Into<Option<String>>
+    {
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        params.insert("symbol".into(), symbol.into());
+        if let Some(code) = contract_code.into() { params.insert("contract_code".into(), code); }
+        if let Some(ctype) = contract_type.into() { params.insert("contract_type".into(), ctype); }
+        if let Some(dir) = direction.into() { params.insert("direction".into(), dir); }
+        if let Some(off) = offset.into() { params.insert("offset".into(), off); }
+
+        Ok(self
+            .transport
+            .signed_post("/api/v1/contract_track_cancelall", Some(params))?
+        )
+    }
+
+    // query track open orders
+    pub fn get_track_open_orders<S1, S2, S3, S4, S5>(
+        &self,
+        symbol: S1,
+        contract_code: S2,
+        trade_type: S3,
+        page_index: S4,
+        page_size: S5,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Page<TrackOpenOrder>>>>>
+    where
+        S1: Into<String>,
+        S2: Into<Option<String>>,
+        S3: Into<Option<u32>>,
+        S4: Into<Option<u32>>,
+        S5: Into<Option<u32>>
+    {
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        params.insert("symbol".into(), symbol.into());
+        if let Some(code) = contract_code.into() { params.insert("contract_code".into(), code); }
+        if let Some(ttype) = trade_type.into() { params.insert("trade_type".into(), format!("{}", ttype)); }
+        if let Some(index) = page_index.into() { params.insert("page_index".into(), format!("{}", index)); }
+        if let Some(size) = page_size.into() { params.insert("page_size".into(), format!("{}", size)); }
+
+        Ok(self
+            .transport
+            .signed_post("/api/v1/contract_track_openorders", Some(params))?
+        )
+    }
+
+    // query track history orders
+    pub fn get_track_his_orders<S1, S2, S3, S4, S5, S6>(
+        &self,
+        symbol: S1,
+        contract_code: S2,
+        status: S3,
+        trade_type: u32,
+        create_date: u32,
+        page_index: S4,
+        page_size: S5,
+        sort_by: S6,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Page<TrackHisOrder>>>>>
+    where
+        S1: Into<String>,
+        S2: Into<Option<String>>,
+        S3: Into<String>,
+        S4: Into<Option<u32>>,
+        S5: Into<Option<u32>>,
+        S6: Into<Option<String>>
+    {
+        let mut params: BTreeMap<String, String> = BTreeMap::new();
+
+        params.insert("symbol".into(), symbol.into());
+        params.insert("status".into(), status.into());
+        params.insert("trade_type".into(), format!("{}", trade_type));
+        params.insert("create_date".into(), format!("{}", create_date));
+
+        if let Some(code) = contract_code.into() { params.insert("contract_code".into(), code); }
+        if let Some(index) = page_index.into() { params.insert("page_index".into(), format!("{}", index)); }
+        if let Some(size) = page_size.into() { params.insert("page_size".into(), format!("{}", size)); }
+        if let Some(sort) = sort_by.into() { params.insert("sort_by".into(), sort); }
+
+        Ok(self
+            .transport
+            .signed_post("/api/v1/contract_track_hisorders", Some(params))?
+        )
+    }
+
     // get transfer limit
     pub fn get_transfer_limit<S1> (
         &self,
@@ -464,9 +1132,13 @@ impl HuobiFuture {
 
         Ok(self
             .transport
-            .signed_post("/v1/futures/transfer", Some(params))?
+            .signed_spot_post("/v1/futures/transfer", Some(params))?
         )
     }
 
 
-}
\ No newline at end of file
+}
+// the comma-separated list form the order endpoints take
+fn join_ids<T: ToString>(ids: &[T]) -> String {
+    ids.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")
+}
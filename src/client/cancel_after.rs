@@ -0,0 +1,94 @@
+use super::HuobiFuture;
+use crate::error::Error;
+use failure::Fallible;
+use futures::future::{abortable, AbortHandle};
+use std::{convert::TryFrom, time::Duration};
+use tracing::*;
+
+// Dead man's switch built on contract-cancel-after.
+//
+// The keeper re-arms the exchange countdown every `refresh` while it is alive. Once it
+// is dropped or a failure is reported through a `KeeperHandle`, refreshing stops and
+// the exchange cancels all open orders when the last countdown expires.
+pub struct CancelAfterKeeper {
+    client: HuobiFuture,
+    handle: KeeperHandle,
+}
+
+#[derive(Clone, Debug)]
+pub struct KeeperHandle {
+    abort: AbortHandle,
+}
+
+impl KeeperHandle {
+    pub fn report_failure(&self) {
+        warn!("[CancelAfter] failure reported, no longer refreshing countdown");
+        self.abort.abort();
+    }
+}
+
+impl CancelAfterKeeper {
+    // `refresh` must be shorter than `time_out`, otherwise the countdown fires while healthy.
+    pub fn spawn(client: HuobiFuture, time_out: Duration, refresh: Duration) -> Fallible<Self> {
+        let invalid = |reason: String| -> failure::Error { Error::InvalidCancelAfter { reason }.into() };
+        if refresh == Duration::from_secs(0) || refresh >= time_out {
+            return Err(invalid(format!("refresh {:?} must be positive and shorter than time_out {:?}", refresh, time_out)));
+        }
+        let time_out_ms = u64::try_from(time_out.as_millis())
+            .map_err(|_| invalid(format!("time_out {:?} is out of range", time_out)))?;
+        let keeper_client = client.clone();
+
+        let task = async move {
+            loop {
+                match keeper_client.cancel_after(true, time_out_ms) {
+                    Ok(req) => match req.await {
+                        Ok(resp) => trace!("[CancelAfter] countdown refreshed: {:?}", resp.data),
+                        Err(e) => warn!("[CancelAfter] refresh failed: {}", e),
+                    },
+                    Err(e) => warn!("[CancelAfter] refresh failed: {}", e),
+                }
+                tokio::time::delay_for(refresh).await;
+            }
+        };
+
+        let (task, abort) = abortable(task);
+        tokio::spawn(task);
+
+        Ok(Self {
+            client,
+            handle: KeeperHandle { abort },
+        })
+    }
+
+    pub fn handle(&self) -> KeeperHandle {
+        self.handle.clone()
+    }
+
+    // Stop refreshing and switch the countdown off, for a clean shutdown.
+    pub async fn disarm(self) -> Fallible<()> {
+        self.handle.abort.abort();
+        self.client.cancel_after(false, None)?.await?;
+        Ok(())
+    }
+}
+
+impl Drop for CancelAfterKeeper {
+    fn drop(&mut self) {
+        self.handle.abort.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_refresh_not_shorter_than_time_out() {
+        let spawn = |time_out: Duration, refresh: Duration| CancelAfterKeeper::spawn(HuobiFuture::new(), time_out, refresh);
+
+        assert!(spawn(Duration::from_secs(60), Duration::from_secs(60)).is_err());
+        assert!(spawn(Duration::from_secs(60), Duration::from_secs(90)).is_err());
+        assert!(spawn(Duration::from_secs(60), Duration::from_secs(0)).is_err());
+        assert!(spawn(Duration::from_secs(u64::MAX), Duration::from_secs(60)).is_err());
+    }
+}
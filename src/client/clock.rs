@@ -0,0 +1,62 @@
+use super::HuobiFuture;
+use crate::time_sync::TimeSync;
+use failure::Fallible;
+use futures::future::{abortable, AbortHandle};
+use std::time::Duration;
+use tracing::*;
+
+// Background refresh of the shared `TimeSync`, stopped when dropped.
+pub struct TimeSyncTask {
+    abort: AbortHandle,
+}
+
+impl Drop for TimeSyncTask {
+    fn drop(&mut self) {
+        self.abort.abort();
+    }
+}
+
+impl HuobiFuture {
+    pub fn time_sync(&self) -> TimeSync {
+        self.transport.time_sync()
+    }
+
+    // Measure the offset between the local and the exchange clock once.
+    pub async fn sync_time(&self) -> Fallible<()> {
+        let sent = chrono::Utc::now();
+        let resp = self.get_server_time()?.await?;
+        let received = chrono::Utc::now();
+
+        match resp.ts {
+            Some(ts) => {
+                let time_sync = self.time_sync();
+                time_sync.update(sent, ts as i64, received);
+                trace!(
+                    "[TimeSync] offset {}ms, round trip {}ms",
+                    time_sync.offset().num_milliseconds(),
+                    time_sync.round_trip().num_milliseconds()
+                );
+                Ok(())
+            }
+            None => Err(failure::format_err!("Server time missing in response")),
+        }
+    }
+
+    pub fn spawn_time_sync(&self, interval: Duration) -> TimeSyncTask {
+        let client = self.clone();
+
+        let task = async move {
+            loop {
+                if let Err(e) = client.sync_time().await {
+                    warn!("[TimeSync] sync failed: {}", e);
+                }
+                tokio::time::delay_for(interval).await;
+            }
+        };
+
+        let (task, abort) = abortable(task);
+        tokio::spawn(task);
+
+        TimeSyncTask { abort }
+    }
+}
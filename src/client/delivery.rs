@@ -0,0 +1,83 @@
+use super::HuobiFuture;
+use crate::{delivery::DeliveryCalendar, error::Error, models::*};
+use failure::Fallible;
+
+impl HuobiFuture {
+    // Refuse order placement while the calendar reports a settlement window.
+    #[must_use]
+    pub fn with_delivery_guard(mut self, calendar: DeliveryCalendar) -> Self {
+        self.delivery_guard = Some(calendar);
+        self
+    }
+
+    // Scoped to the traded symbol, taken from `symbol` or else `contract_code`.
+    pub(crate) fn check_settlement(&self, symbol: Option<&str>, contract_code: Option<&str>) -> Fallible<()> {
+        if let Some(calendar) = &self.delivery_guard {
+            let symbol = symbol
+                .map(str::to_string)
+                .or_else(|| contract_code.map(|code| ContractCode::from(code).symbol().to_string()));
+            if let Some(until) = calendar.settlement_window(symbol.as_deref(), chrono::Utc::now()) {
+                return Err(Error::SettlementWindow { until }.into());
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_order_settlement(&self, order: &OrderRequest) -> Fallible<()> {
+        self.check_settlement(order.symbol.as_deref(), order.contract_code.as_ref().map(ContractCode::as_str))
+    }
+
+    // Close `position` and reopen the same volume and direction on the next expiry,
+    // refreshing the calendar first if one of the symbol's contracts has delivered.
+    // Errors mean nothing was closed; once the close leg is placed, a failed open leg is
+    // reported in `RollOutcome::open`.
+    pub async fn roll_position(
+        &self,
+        calendar: &DeliveryCalendar,
+        position: &Position,
+        order_price_type: &str,
+    ) -> Fallible<RollOutcome> {
+        let now = chrono::Utc::now();
+        if calendar.needs_refresh_for(&position.symbol, now) {
+            calendar.refresh(self, &position.symbol).await?;
+        }
+        let next = calendar
+            .next_contract(&position.contract_code, now)
+            .ok_or(Error::SymbolNotFound)?;
+        let volume = position.volume as u32;
+        let closing_direction = if position.direction == "buy" { "sell" } else { "buy" };
+
+        let close = self
+            .place_order(
+                None, None, position.contract_code.to_string(), None, None, volume,
+                closing_direction, "close", position.lever_rate, order_price_type, None,
+            )?
+            .await?;
+        let close = order_id(close)?;
+
+        let open = match self.place_order(
+            None, None, next.to_string(), None, None, volume,
+            position.direction.as_str(), "open", position.lever_rate, order_price_type, None,
+        ) {
+            Ok(fut) => fut.await.and_then(order_id),
+            Err(e) => Err(e),
+        };
+        let open = open.map_err(|e| e.to_string());
+
+        Ok(RollOutcome { from: position.contract_code.clone(), to: next, close, open })
+    }
+}
+
+fn order_id(resp: APIResponse<OrderId>) -> Fallible<OrderId> {
+    match resp.data {
+        Some(id) => Ok(id),
+        None => {
+            let code = match resp.err_code {
+                Some(ErrCodeEnum::U(code)) => i64::from(code),
+                Some(ErrCodeEnum::S(code)) => code.parse().unwrap_or(-1),
+                None => -1,
+            };
+            Err(Error::HuobiError { code, msg: resp.err_msg.unwrap_or_default() }.into())
+        }
+    }
+}
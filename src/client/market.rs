@@ -7,6 +7,44 @@ use futures::prelude::*;
 use std::{collections::BTreeMap};
 
 impl HuobiFuture {
+    // Get Server Timestamp
+    pub fn get_server_time(
+        &self,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<()>>>>
+    {
+        Ok(self
+            .transport
+            .get::<_, ()>("/api/v1/timestamp", None)?)
+    }
+
+    // Get System Heartbeat
+    pub fn get_heartbeat(
+        &self,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Heartbeat>>>>
+    {
+        Ok(self
+            .transport
+            .get::<_, ()>("/heartbeat/", None)?)
+    }
+
+    // Get Api State
+    pub fn get_api_state<S1>(
+        &self,
+        symbol: S1,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Vec<ApiState>>>>>
+    where
+        S1: Into<Option<String>>
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+
+        if let Some(sym) = symbol.into() { parameters.insert("symbol".into(), sym); }
+
+        Ok(self
+            .transport
+            .get("/api/v1/contract_api_state", Some(parameters))?
+        )
+    }
+
     // Get contract information (contract metadata etc)
     pub fn get_contract_info<S1, S2, S3>(
         &self,
@@ -174,4 +212,271 @@ impl HuobiFuture {
         )
     }
 
+    // Get Batch Merged Data
+    pub fn get_batch_merged<S1>(
+        &self,
+        symbol: S1,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Vec<BatchMerged>>>>>
+    where
+        S1: Into<Option<String>>
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+
+        if let Some(sym) = symbol.into() { parameters.insert("symbol".into(), sym); }
+
+        Ok(self
+            .transport
+            .get("/market/detail/batch_merged", Some(parameters))?
+        )
+    }
+
+    // Get Last Trade
+    pub fn get_market_trade<S1>(
+        &self,
+        symbol: S1,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<TradeDetail>>>>
+    where
+        S1: Into<String>
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+
+        parameters.insert("symbol".into(), symbol.into());
+
+        Ok(self
+            .transport
+            .get("/market/trade", Some(parameters))?
+        )
+    }
+
+    // Get History Trades
+    pub fn get_history_trades<S1>(
+        &self,
+        symbol: S1,
+        size: u32,
+    ) -> Fallible<impl Future<Output = Fallible<HistoryTrade>>>
+    where
+        S1: Into<String>
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+
+        parameters.insert("symbol".into(), symbol.into());
+        parameters.insert("size".into(), format!("{}", size));
+
+        Ok(self
+            .transport
+            .get("/market/history/trade", Some(parameters))?
+        )
+    }
+
+    // Get Open Interest
+    pub fn get_open_interest<S1, S2, S3>(
+        &self,
+        symbol: S1,
+        contract_type: S2,
+        contract_code: S3,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Vec<OpenInterest>>>>>
+    where
+        S1: Into<Option<String>>,
+        S2: Into<Option<String>>,
+        S3: Into<Option<String>>
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+
+        if let Some(sym) = symbol.into() { parameters.insert("symbol".into(), sym); }
+        if let Some(ctype) = contract_type.into() { parameters.insert("contract_type".into(), ctype); }
+        if let Some(code) = contract_code.into() { parameters.insert("contract_code".into(), code); }
+
+        Ok(self
+            .transport
+            .get("/api/v1/contract_open_interest", Some(parameters))?
+        )
+    }
+
+    // Get History Open Interest
+    pub fn get_his_open_interest<S1, S2, S3, S4>(
+        &self,
+        symbol: S1,
+        contract_type: S2,
+        period: S3,
+        size: S4,
+        amount_type: u32,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<HisOpenInterest>>>>
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<String>,
+        S4: Into<Option<u32>>
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+
+        parameters.insert("symbol".into(), symbol.into());
+        parameters.insert("contract_type".into(), contract_type.into());
+        parameters.insert("period".into(), period.into());
+        parameters.insert("amount_type".into(), format!("{}", amount_type));
+
+        if let Some(sz) = size.into() { parameters.insert("size".into(), format!("{}", sz)); }
+
+        Ok(self
+            .transport
+            .get("/api/v1/contract_his_open_interest", Some(parameters))?
+        )
+    }
+
+    // Get Elite Account Ratio
+    pub fn get_elite_account_ratio<S1, S2>(
+        &self,
+        symbol: S1,
+        period: S2,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<EliteRatio>>>>
+    where
+        S1: Into<String>,
+        S2: Into<String>
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+
+        parameters.insert("symbol".into(), symbol.into());
+        parameters.insert("period".into(), period.into());
+
+        Ok(self
+            .transport
+            .get("/api/v1/contract_elite_account_ratio", Some(parameters))?
+        )
+    }
+
+    // Get Elite Position Ratio
+    pub fn get_elite_position_ratio<S1, S2>(
+        &self,
+        symbol: S1,
+        period: S2,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<EliteRatio>>>>
+    where
+        S1: Into<String>,
+        S2: Into<String>
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+
+        parameters.insert("symbol".into(), symbol.into());
+        parameters.insert("period".into(), period.into());
+
+        Ok(self
+            .transport
+            .get("/api/v1/contract_elite_position_ratio", Some(parameters))?
+        )
+    }
+
+    // Get Insurance Fund
+    pub fn get_insurance_fund<S1, S2, S3>(
+        &self,
+        symbol: S1,
+        page_index: S2,
+        page_size: S3,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<InsuranceFund>>>>
+    where
+        S1: Into<String>,
+        S2: Into<Option<u32>>,
+        S3: Into<Option<u32>>
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+
+        parameters.insert("symbol".into(), symbol.into());
+
+        if let Some(index) = page_index.into() { parameters.insert("page_index".into(), format!("{}", index)); }
+        if let Some(size) = page_size.into() { parameters.insert("page_size".into(), format!("{}", size)); }
+
+        Ok(self
+            .transport
+            .get("/api/v1/contract_insurance_fund", Some(parameters))?
+        )
+    }
+
+    // Get Adjust Factor
+    pub fn get_adjust_factor<S1>(
+        &self,
+        symbol: S1,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Vec<AdjustFactor>>>>>
+    where
+        S1: Into<Option<String>>
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+
+        if let Some(sym) = symbol.into() { parameters.insert("symbol".into(), sym); }
+
+        Ok(self
+            .transport
+            .get("/api/v1/contract_adjustfactor", Some(parameters))?
+        )
+    }
+
+    // Get Risk Info
+    pub fn get_risk_info<S1>(
+        &self,
+        symbol: S1,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Vec<RiskInfo>>>>>
+    where
+        S1: Into<Option<String>>
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+
+        if let Some(sym) = symbol.into() { parameters.insert("symbol".into(), sym); }
+
+        Ok(self
+            .transport
+            .get("/api/v1/contract_risk_info", Some(parameters))?
+        )
+    }
+
+    // Get Index Price
+    pub fn get_index_price<S1>(
+        &self,
+        symbol: S1,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Vec<IndexPrice>>>>>
+    where
+        S1: Into<Option<String>>
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+
+        if let Some(sym) = symbol.into() { parameters.insert("symbol".into(), sym); }
+
+        Ok(self
+            .transport
+            .get("/api/v1/contract_index", Some(parameters))?
+        )
+    }
+
+    // Get Delivery Price
+    pub fn get_delivery_price<S1>(
+        &self,
+        symbol: S1,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<DeliveryPrice>>>>
+    where
+        S1: Into<String>
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+
+        parameters.insert("symbol".into(), symbol.into());
+
+        Ok(self
+            .transport
+            .get("/api/v1/contract_delivery_price", Some(parameters))?
+        )
+    }
+
+    // Get Estimated Settlement Price
+    pub fn get_estimated_settlement_price<S1>(
+        &self,
+        symbol: S1,
+    ) -> Fallible<impl Future<Output = Fallible<APIResponse<Vec<EstimatedSettlementPrice>>>>>
+    where
+        S1: Into<Option<String>>
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+
+        if let Some(sym) = symbol.into() { parameters.insert("symbol".into(), sym); }
+
+        Ok(self
+            .transport
+            .get("/api/v1/contract_estimated_settlement_price", Some(parameters))?
+        )
+    }
+
 }
@@ -1,13 +1,33 @@
 mod account;
+pub mod cancel_after;
+pub mod clock;
+mod delivery;
 mod market;
+pub mod pre_trade;
+mod replace;
+pub mod status;
 mod subscription;
 pub mod websocket;
 
-use crate::transport::Transport;
+pub use account::{BATCH_ORDER_MAX, CANCEL_ORDER_MAX};
+
+use crate::{
+    client_order_id::{ClientOrderId, ClientOrderIdGenerator},
+    delivery::DeliveryCalendar,
+    pre_trade::PreTradeChecker, rate_limit::RateLimiter, signer::Signer, status::SystemStatus,
+    transport::Transport,
+};
+use std::sync::Arc;
+use tokio::sync::watch;
 
 #[derive(Clone, Default)]
 pub struct HuobiFuture {
     pub transport: Transport,
+    status_guard: Option<watch::Receiver<SystemStatus>>,
+    pre_trade: Option<PreTradeChecker>,
+    rate_limiter: Option<RateLimiter>,
+    id_generator: Option<Arc<ClientOrderIdGenerator>>,
+    delivery_guard: Option<DeliveryCalendar>,
 }
 
 impl HuobiFuture {
@@ -20,6 +40,51 @@ impl HuobiFuture {
     pub fn with_credential(api_key: &str, api_secret: &str) -> Self {
         Self {
             transport: Transport::with_credential(api_key, api_secret),
+            status_guard: None,
+            pre_trade: None,
+            rate_limiter: None,
+            id_generator: None,
+            delivery_guard: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_signer(signer: Arc<dyn Signer>) -> Self {
+        Self {
+            transport: Transport::with_signer(signer),
+            status_guard: None,
+            pre_trade: None,
+            rate_limiter: None,
+            id_generator: None,
+            delivery_guard: None,
+        }
+    }
+
+    // Pace the chunked batch helpers, e.g. to the exchange's per-UID trade limit.
+    #[must_use]
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    // Source of client order ids filled in when an order omits one; defaults to
+    // the process-wide generator.
+    #[must_use]
+    pub fn with_id_generator(mut self, generator: ClientOrderIdGenerator) -> Self {
+        self.id_generator = Some(Arc::new(generator));
+        self
+    }
+
+    pub fn next_client_order_id(&self) -> ClientOrderId {
+        match &self.id_generator {
+            Some(generator) => generator.next(),
+            None => ClientOrderId::next(),
+        }
+    }
+
+    pub(crate) async fn acquire_rate(&self) {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire().await;
         }
     }
 }
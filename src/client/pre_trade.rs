@@ -0,0 +1,137 @@
+use super::HuobiFuture;
+use crate::{
+    client_order_id::ClientOrderId,
+    models::{APIResponse, OrderRequest},
+    pre_trade::PreTradeChecker,
+};
+use failure::Fallible;
+use std::future::Future;
+
+impl HuobiFuture {
+    // Run every placed order through the checker before it is sent.
+    #[must_use]
+    pub fn with_pre_trade(mut self, checker: PreTradeChecker) -> Self {
+        self.pre_trade = Some(checker);
+        self
+    }
+
+    pub fn pre_trade(&self) -> Option<&PreTradeChecker> {
+        self.pre_trade.as_ref()
+    }
+
+    pub(crate) fn check_pre_trade(&self, orders: &mut [OrderRequest]) -> Fallible<()> {
+        match &self.pre_trade {
+            Some(checker) => checker.check(orders),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn pending_orders(&self, ids: Vec<Option<ClientOrderId>>) -> PendingOrders {
+        PendingOrders { checker: self.pre_trade.clone(), ids }
+    }
+}
+
+// Orders accepted by the checker whose request hasn't completed. Dropping it releases
+// them all, so a request that fails to build, or a future dropped before it finishes,
+// doesn't leave them counted against the limits.
+pub(crate) struct PendingOrders {
+    checker: Option<PreTradeChecker>,
+    ids: Vec<Option<ClientOrderId>>,
+}
+
+impl PendingOrders {
+    // Await the request, then release the orders the exchange did not accept. `rejected`
+    // picks the failed positions out of a successful response; a failed request
+    // releases all of them.
+    pub(crate) fn settle<F, T, R>(mut self, fut: F, rejected: R) -> impl Future<Output = Fallible<APIResponse<T>>>
+    where
+        F: Future<Output = Fallible<APIResponse<T>>>,
+        R: FnOnce(&APIResponse<T>) -> Vec<usize>,
+    {
+        async move {
+            let result = fut.await;
+            let failed = match &result {
+                Ok(resp) if resp.status.as_deref() == Some("ok") => rejected(resp),
+                _ => (0..self.ids.len()).collect(),
+            };
+            if let Some(checker) = &self.checker {
+                for id in failed.into_iter().filter_map(|i| self.ids.get(i).copied().flatten()) {
+                    checker.release(id);
+                }
+            }
+            // the rest are working on the exchange
+            self.ids.clear();
+            result
+        }
+    }
+}
+
+impl Drop for PendingOrders {
+    fn drop(&mut self) {
+        if let Some(checker) = &self.checker {
+            for id in self.ids.iter().flatten() {
+                checker.release(*id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::Symbol, pre_trade::{ContractLimits, TickPolicy}};
+
+    fn checker() -> PreTradeChecker {
+        let checker = PreTradeChecker::new(TickPolicy::Reject);
+        let contract: Symbol = serde_json::from_value(serde_json::json!({
+            "symbol": "BTC", "contract_code": "BTC200925", "contract_type": "quarter", "contract_size": 100.0,
+            "price_tick": 0.01, "delivery_date": "20200925", "create_date": "20200612", "contract_status": 1
+        }))
+        .unwrap();
+        checker.set_contracts(&[contract]);
+        checker.set_limits("BTC", ContractLimits { max_position: Some(10.0), max_notional: None });
+        checker
+    }
+
+    fn place(client: &HuobiFuture) -> Fallible<impl Future<Output = Fallible<APIResponse<crate::models::OrderId>>>> {
+        client.place_order(
+            None, None, "BTC200925".to_string(), None, 8000.0, 10, "buy", "open", 10, "limit", None,
+        )
+    }
+
+    fn has_room(checker: &PreTradeChecker) -> bool {
+        let mut order = OrderRequest {
+            contract_code: Some("BTC200925".into()),
+            symbol: None,
+            contract_type: None,
+            client_order_id: None,
+            price: Some(8000.0),
+            volume: 10,
+            direction: "buy".into(),
+            offset: "open".into(),
+            lever_rate: 10,
+            order_price_type: "limit".into(),
+            tpsl: None,
+        };
+        checker.check(std::slice::from_mut(&mut order)).is_ok()
+    }
+
+    #[test]
+    fn request_build_failure_releases_the_order() {
+        let checker = checker();
+        // no key, so signing fails before a request exists
+        let client = HuobiFuture::new().with_pre_trade(checker.clone());
+        assert!(place(&client).is_err());
+        assert!(has_room(&checker));
+    }
+
+    #[test]
+    fn dropped_request_releases_the_order() {
+        let checker = checker();
+        let client = HuobiFuture::with_credential("key", "secret").with_pre_trade(checker.clone());
+        let fut = place(&client).unwrap();
+        assert!(!has_room(&checker));
+        drop(fut);
+        assert!(has_room(&checker));
+    }
+}
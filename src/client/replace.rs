@@ -0,0 +1,186 @@
+use super::HuobiFuture;
+use crate::{error::Error, models::*};
+use failure::Fallible;
+use std::{future::Future, time::Duration};
+use tracing::*;
+
+// Order info polls while waiting for a cancel to settle.
+const CONFIRM_ATTEMPTS: usize = 10;
+const CONFIRM_INTERVAL: Duration = Duration::from_millis(200);
+
+impl HuobiFuture {
+    // Cancel `order_id`, wait until the exchange reports it cancelled or filled, then
+    // place `new_order` under a fresh client order id, replacing any it carries. Fills
+    // that land during the swap are reported; if the old order filled completely nothing
+    // is placed.
+    pub async fn replace_order(
+        &self,
+        symbol: &str,
+        order_id: u64,
+        new_order: OrderRequest,
+    ) -> Fallible<ReplaceOutcome> {
+        self.check_maintenance()?;
+        self.check_settlement(Some(symbol), None)?;
+
+        let before = self.order_info(symbol, order_id).await?;
+
+        // a rejected cancel usually means the order already finished; the poll below decides
+        match self.cancel_orders(symbol.to_string(), order_id.to_string(), &[])?.await {
+            Ok(resp) => {
+                for e in resp.data.map(|cancel| cancel.errors).unwrap_or_default() {
+                    warn!("[Replace] cancel of {} rejected: {} {}", e.order_id, e.err_code, e.err_msg);
+                }
+            }
+            Err(e) => warn!("[Replace] cancel of {} failed: {}", order_id, e),
+        }
+
+        let old_order =
+            confirm_terminal(order_id, CONFIRM_INTERVAL, || self.order_info(symbol, order_id)).await?;
+        let filled_during_swap = old_order.trade_volume.saturating_sub(before.trade_volume);
+
+        let new_order = match self.replacement(&old_order, new_order) {
+            Some(order) => order,
+            None => {
+                return Ok(ReplaceOutcome { old_order, filled_during_swap, new_order: None, new_client_order_id: None })
+            }
+        };
+        let client_order_id = new_order.client_order_id;
+        let placed = self
+            .place_orders_chunked(vec![new_order])
+            .await?
+            .pop()
+            .map(|outcome| outcome.result);
+
+        match placed {
+            Some(BatchOrderResult::Placed(id)) => Ok(ReplaceOutcome {
+                old_order,
+                filled_during_swap,
+                new_order: Some(id),
+                new_client_order_id: client_order_id,
+            }),
+            Some(BatchOrderResult::Rejected(e)) => {
+                Err(Error::HuobiError { code: i64::from(e.err_code), msg: e.err_msg }.into())
+            }
+            Some(BatchOrderResult::Failed(reason)) => Err(failure::err_msg(reason)),
+            None => Err(failure::err_msg("empty batch response")),
+        }
+    }
+
+    // The order to place in place of `old_order`, or `None` if it filled completely.
+    fn replacement(&self, old_order: &Order, mut new_order: OrderRequest) -> Option<OrderRequest> {
+        if old_order.status == OrderStatus::Filled {
+            return None;
+        }
+        // never reuse the id a caller copied from the old order
+        new_order.client_order_id = Some(self.next_client_order_id());
+        Some(new_order)
+    }
+
+    async fn order_info(&self, symbol: &str, order_id: u64) -> Fallible<Order> {
+        let resp = self.get_order_info(symbol.to_string(), order_id.to_string(), &[])?.await?;
+        resp.data
+            .and_then(|orders| orders.into_iter().find(|o| o.order_id == order_id))
+            .ok_or_else(|| failure::err_msg(format!("order {} not found", order_id)))
+    }
+}
+
+// Poll `info` until the order reaches a terminal status, giving up after CONFIRM_ATTEMPTS.
+async fn confirm_terminal<F, Fut>(order_id: u64, interval: Duration, mut info: F) -> Fallible<Order>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Fallible<Order>>,
+{
+    for _ in 0..CONFIRM_ATTEMPTS {
+        let order = info().await?;
+        if order.status.is_terminal() {
+            return Ok(order);
+        }
+        tokio::time::delay_for(interval).await;
+    }
+    Err(Error::CancelNotConfirmed { order_id }.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client_order_id::{ClientOrderId, ClientOrderIdGenerator};
+    use serde_json::json;
+    use std::cell::Cell;
+
+    fn order(status: u32, trade_volume: u32) -> Order {
+        serde_json::from_value(json!({
+            "symbol": "BTC", "contract_type": "quarter", "contract_code": "BTC200925", "volume": 5.0,
+            "price": 8000.0, "order_price_type": "limit", "direction": "buy", "offset": "open",
+            "lever_rate": 10, "order_id": 1, "order_id_str": "1", "client_order_id": 42,
+            "created_at": 0, "canceled_at": null, "trade_volume": trade_volume, "trade_turnover": 0.0,
+            "fee": 0.0, "fee_asset": "BTC", "trade_avg_price": null, "margin_frozen": 0.0, "profit": 0.0,
+            "status": status, "order_type": 1, "order_source": "api", "liquidation_type": "0"
+        }))
+        .unwrap()
+    }
+
+    fn request() -> OrderRequest {
+        OrderRequest {
+            contract_code: Some("BTC200925".into()),
+            symbol: None,
+            contract_type: None,
+            // copied from the old order
+            client_order_id: Some(ClientOrderId(42)),
+            price: Some(8100.0),
+            volume: 5,
+            direction: "buy".into(),
+            offset: "open".into(),
+            lever_rate: 10,
+            order_price_type: "limit".into(),
+            tpsl: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn poll_stops_at_the_first_terminal_status() {
+        let statuses = [3, 11, 7];
+        let calls = Cell::new(0);
+        let info = || {
+            let status = statuses[calls.get()];
+            calls.set(calls.get() + 1);
+            async move { Ok(order(status, 0)) }
+        };
+
+        let old_order = confirm_terminal(1, Duration::from_millis(1), info).await.unwrap();
+        assert_eq!(old_order.status, OrderStatus::Cancelled);
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn unconfirmed_cancel_is_an_error() {
+        let calls = Cell::new(0);
+        let info = || {
+            calls.set(calls.get() + 1);
+            async { Ok(order(11, 0)) }
+        };
+
+        let err = confirm_terminal(1, Duration::from_millis(1), info).await.unwrap_err();
+        match err.downcast_ref::<Error>() {
+            Some(Error::CancelNotConfirmed { order_id: 1 }) => {}
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert_eq!(calls.get(), CONFIRM_ATTEMPTS);
+    }
+
+    #[test]
+    fn fully_filled_order_is_not_replaced() {
+        let client = HuobiFuture::new();
+        assert!(client.replacement(&order(6, 5), request()).is_none());
+    }
+
+    #[test]
+    fn replacement_gets_a_fresh_client_order_id() {
+        let client = HuobiFuture::new().with_id_generator(ClientOrderIdGenerator::new(7).unwrap());
+
+        for status in &[5, 7] {
+            let id = client.replacement(&order(*status, 2), request()).unwrap().client_order_id.unwrap();
+            assert_ne!(id, ClientOrderId(42));
+            assert_eq!(id.value() / 10_000 % 100, 7);
+        }
+    }
+}
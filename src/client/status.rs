@@ -0,0 +1,69 @@
+use super::HuobiFuture;
+use crate::{
+    error::Error,
+    status::{StatusMonitor, FUTURES_SERVICE},
+};
+use failure::Fallible;
+use futures::future::{abortable, AbortHandle};
+use std::time::Duration;
+use tracing::*;
+
+// Background `/heartbeat/` polling, stopped when dropped.
+pub struct StatusPollTask {
+    abort: AbortHandle,
+}
+
+impl Drop for StatusPollTask {
+    fn drop(&mut self) {
+        self.abort.abort();
+    }
+}
+
+impl HuobiFuture {
+    // Refuse order placement while the monitor reports futures maintenance.
+    #[must_use]
+    pub fn with_status_guard(mut self, monitor: &StatusMonitor) -> Self {
+        self.status_guard = Some(monitor.subscribe());
+        self
+    }
+
+    pub(crate) fn check_maintenance(&self) -> Fallible<()> {
+        if let Some(rx) = &self.status_guard {
+            if let Some(status) = rx.borrow().maintenance(FUTURES_SERVICE) {
+                return Err(Error::Maintenance {
+                    service: FUTURES_SERVICE.to_string(),
+                    estimated_recovery_time: status.estimated_recovery_time,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn spawn_status_polling(&self, monitor: StatusMonitor, interval: Duration) -> StatusPollTask {
+        let client = self.clone();
+
+        let task = async move {
+            loop {
+                match client.get_heartbeat() {
+                    Ok(req) => match req.await {
+                        Ok(resp) => {
+                            if let Some(hb) = resp.data {
+                                let ts = resp.ts.unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64);
+                                monitor.apply_heartbeat(&hb, ts);
+                            }
+                        }
+                        Err(e) => warn!("[Status] heartbeat poll failed: {}", e),
+                    },
+                    Err(e) => warn!("[Status] heartbeat poll failed: {}", e),
+                }
+                tokio::time::delay_for(interval).await;
+            }
+        };
+
+        let (task, abort) = abortable(task);
+        tokio::spawn(task);
+
+        StatusPollTask { abort }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{
+    error::Error,
     models::*, 
     client::websocket::HuobiWebsocket,
     client::websocket::WS_HOST,
@@ -6,11 +7,12 @@ use crate::{
 use std::{
     collections::HashMap,
 };
+use crate::signer::sign_request;
 use failure::Fallible;
 use futures::prelude::*;
+use http::Method;
 use serde_json::{json};
-use std::{collections::BTreeMap};
-use ring::{digest, hmac};
+use tracing::*;
 
 impl HuobiWebsocket {
 
@@ -18,6 +20,28 @@ impl HuobiWebsocket {
         &mut self,
         subs: HashMap<Subscription, Vec<&str>>,
     ) -> Fallible<()> {
+        let result = self.run(subs).await;
+
+        // the connection loop only returns when the websocket has failed
+        if let Err(e) = &result {
+            error!("[Websocket] connection failed: {}", e);
+        }
+        for handle in &self.failure_handles {
+            handle.report_failure();
+        }
+
+        result
+    }
+
+    async fn run(
+        &mut self,
+        subs: HashMap<Subscription, Vec<&str>>,
+    ) -> Fallible<()> {
+        // fail before opening any connection rather than halfway through
+        if subs.contains_key(&Subscription::Account) {
+            self.check_key()?;
+        }
+
         for (subscription, topics) in &subs {
             if *subscription == Subscription::Market {
                 self.subscribe(Subscription::Market).await?;
@@ -25,17 +49,13 @@ impl HuobiWebsocket {
             }
             if *subscription == Subscription::Account {
                 self.subscribe(Subscription::Account).await?;
-                let mut params: BTreeMap<String, String> = BTreeMap::new();
-                let signature = self.generate_signature(&mut params);
-                let message = json!({
-                    "AccessKeyId": params.get(&"AccessKeyId".to_string()),
-                    "SignatureMethod": params.get(&"SignatureMethod".to_string()),
-                    "SignatureVersion": params.get(&"SignatureVersion".to_string()),
-                    "Timestamp": params.get(&"Timestamp".to_string()),
-                    "Signature": signature,
+                let mut message = json!({
                     "op": "auth".to_string(),
                     "type": "api".to_string(),   
                 });
+                for (key, value) in self.generate_signature().await? {
+                    message[key] = value.into();
+                }
 
                 let sink = self.sinks.get_mut(&Subscription::Account).unwrap();
                 sink.send(tungstenite::Message::Text(message.to_string())).await?;
@@ -44,6 +64,10 @@ impl HuobiWebsocket {
                 self.subscribe(Subscription::Index).await?;
                 self.sub_index(topics).await?;
             }
+            if *subscription == Subscription::CenterNotification {
+                self.subscribe(Subscription::CenterNotification).await?;
+                self.sub_notification(topics).await?;
+            }
         }
 
         self.rx_handler(&subs).await?;
@@ -92,6 +116,20 @@ impl HuobiWebsocket {
         Ok(())
     }
 
+    async fn sub_notification(&mut self, topics: &[&str]) -> Fallible<()> {
+        for topic in topics {
+            let message = json!({
+                "op": "sub",
+                "cid": "huobifuture-rust-async",
+                "topic": topic,
+                });
+            let sink = self.sinks.get_mut(&Subscription::CenterNotification).unwrap();
+            sink.send(tungstenite::Message::Text(message.to_string())).await?;
+        }
+
+        Ok(())
+    }
+
     async fn sub_index(&mut self, topics: &[&str]) -> Fallible<()> {
         for topic in topics {
             let message = json!({
@@ -107,7 +145,18 @@ impl HuobiWebsocket {
 
 
     async fn rx_handler(&mut self, subs: &HashMap<Subscription, Vec<&str>>) -> Fallible<()> {
-        while let Some(msg) = self.try_next().await? {
+        while let Some(msg) = self.next().await {
+            // a single bad frame must not take the connection down
+            let msg = match msg {
+                Ok(msg) => msg,
+                Err(e) => match e.downcast_ref::<Error>() {
+                    Some(Error::WebsocketDecode { raw, .. }) => {
+                        warn!("[Websocket] dropping undecodable frame: {}: {:?}", e, raw);
+                        continue;
+                    }
+                    _ => return Err(e),
+                },
+            };
             match msg {
                 WebsocketEvent::IncrementalOrderBook(msg) => (self.handler)(WebsocketEvent::IncrementalOrderBook(msg))?,
                 WebsocketEvent::OrderBook(msg) => (self.handler)(WebsocketEvent::OrderBook(msg))?,
@@ -122,7 +171,7 @@ impl HuobiWebsocket {
                     });
                     //println!("### pong: {:?}", message);
                     for sub in subs.keys() {
-                        if *sub == Subscription::Account {
+                        if *sub == Subscription::Account || *sub == Subscription::CenterNotification {
                             continue;
                         }
                         let sink = self.sinks.get_mut(sub).unwrap();
@@ -136,12 +185,15 @@ impl HuobiWebsocket {
                 WebsocketEvent::Liquidation(msg) => (self.handler)(WebsocketEvent::Liquidation(msg))?,
                 WebsocketEvent::ContractInfo(msg) => (self.handler)(WebsocketEvent::ContractInfo(msg))?,
                 WebsocketEvent::TriggerOrder(msg) => (self.handler)(WebsocketEvent::TriggerOrder(msg))?,
+                WebsocketEvent::TrackOrder(msg) => (self.handler)(WebsocketEvent::TrackOrder(msg))?,
                 WebsocketEvent::Basis(msg) => (self.handler)(WebsocketEvent::Basis(msg))?,
                 WebsocketEvent::Index(msg) => (self.handler)(WebsocketEvent::Index(msg))?,
+                WebsocketEvent::ServiceHeartbeat(msg) => (self.handler)(WebsocketEvent::ServiceHeartbeat(msg))?,
                 WebsocketEvent::Ping => { println!("### Ping {:?}", msg)},
                 WebsocketEvent::Pong => { println!("### Pong {:?}", msg)},
                 WebsocketEvent::Binary(msg) => { println!("### Binary {:?} ", msg)},
                 WebsocketEvent::Text(msg) => { println!("### Text {:?} ", msg)},
+                WebsocketEvent::Raw(msg) => (self.handler)(WebsocketEvent::Raw(msg))?,
                 WebsocketEvent::OpStatus(msg) => { 
                     // println!("### Op Status {:?}", msg);
                     if msg.op == "ping" {
@@ -151,8 +203,11 @@ impl HuobiWebsocket {
                             "ts": ts,       
                         });
                         //println!("### op pong: {:?}", message);
-                        let sink = self.sinks.get_mut(&Subscription::Account).unwrap();
-                        sink.send(tungstenite::Message::Text(message.to_string())).await?;
+                        for sub in &[Subscription::Account, Subscription::CenterNotification] {
+                            if let Some(sink) = self.sinks.get_mut(sub) {
+                                sink.send(tungstenite::Message::Text(message.to_string())).await?;
+                            }
+                        }
     
                     }
                     if msg.op == "auth" {
@@ -172,48 +227,10 @@ impl HuobiWebsocket {
     }
 
 
-    fn generate_signature(&mut self, params: & mut BTreeMap<String, String>) -> String
+    async fn generate_signature(&self) -> Fallible<Vec<(String, String)>>
     {
-        let (key, secret) = self.check_key().expect("no key");
-        params.insert("AccessKeyId".to_string(), key.to_string());
-        params.insert("SignatureMethod".to_string(), "HmacSHA256".to_string());
-        params.insert("SignatureVersion".to_string(), "2".to_string());
-        let utc_time = chrono::Utc::now();
-        let utctimes = utc_time.format("%Y-%m-%dT%H:%M:%S").to_string();
-        params.insert("Timestamp".to_string(), utctimes); 
-
-        let build_params = build_query_string(params.clone());
-
-        let format_str = format!("{}\n{}\n{}\n{}", "GET", WS_HOST, "/notification", build_params,); 
-
-        sign_hmac_sha256_base64(
-                    secret,
-                    &format_str,
-            )
+        let signer = self.check_key()?;
 
+        sign_request(&*signer, &Method::GET, WS_HOST, "/notification", Vec::new(), &self.time_sync.timestamp()).await
     }
 }
-
-pub fn build_query_string(parameters: BTreeMap<String, String>) -> String {
-    parameters
-        .into_iter()
-        .map(|(key, value)| format!("{}={}", key, percent_encode(&value)))
-        .collect::<Vec<String>>()
-        .join("&")
-}
-
-pub fn sign_hmac_sha256_base64(secret: &str, digest: &str) -> String {
-    use data_encoding::BASE64;
-
-    let signed_key = hmac::SigningKey::new(&digest::SHA256, secret.as_bytes());
-    let signature = hmac::sign(&signed_key, digest.as_bytes());
-    BASE64.encode(signature.as_ref())
-}
-
-pub fn percent_encode(source: &str) -> String {
-    use percent_encoding::{define_encode_set, utf8_percent_encode, USERINFO_ENCODE_SET};
-    define_encode_set! {
-        pub CUSTOM_ENCODE_SET = [USERINFO_ENCODE_SET] | { '+', ',' }
-    }
-    utf8_percent_encode(source, CUSTOM_ENCODE_SET).to_string()
-}
\ No newline at end of file
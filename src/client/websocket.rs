@@ -1,13 +1,17 @@
 use crate::{
-    error::Error,
+    client::{cancel_after::KeeperHandle, HuobiFuture},
+    decode::decode_event,
+    error::{DecodeErrorKind, Error},
     models::*,
+    signer::{hmac_signer, Signer},
+    time_sync::TimeSync,
 };
 use failure::Fallible;
 use futures::{prelude::*, stream::SplitStream, stream::SplitSink};
-use serde_json::from_str;
 use std::{
     collections::HashMap,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 use streamunordered::{StreamUnordered, StreamYield};
@@ -30,34 +34,98 @@ pub type StoredSink = SplitSink<WSStream, tungstenite::Message>;
 
 #[allow(clippy::module_name_repetitions)]
 pub struct HuobiWebsocket  {
-    credential: Option<(String, String)>,
+    signer: Option<Arc<dyn Signer>>,
     subscriptions: HashMap<Subscription, usize>,
     tokens: HashMap<usize, Subscription>,
     streams: StreamUnordered<StoredStream>,
     pub sinks: HashMap<Subscription, StoredSink>,
     pub handler: Box<dyn FnMut(WebsocketEvent) -> Fallible<()>>,
+    pub(crate) failure_handles: Vec<KeeperHandle>,
+    pub(crate) time_sync: TimeSync,
 }
 
 impl HuobiWebsocket {
     pub fn new<Callback: 'static>(api_key: &str, api_secret: &str, handler: Callback) -> Self
+    where
+        Callback: FnMut(WebsocketEvent) -> Fallible<()>
+    {
+        Self::with_credential(api_key, api_secret, handler)
+    }
+
+    // Market, index and system status data only; Account subscriptions fail with `NoApiKeySet`.
+    pub fn public<Callback: 'static>(handler: Callback) -> Self
+    where
+        Callback: FnMut(WebsocketEvent) -> Fallible<()>
+    {
+        Self::build(None, handler)
+    }
+
+    pub fn with_credential<Callback: 'static>(api_key: &str, api_secret: &str, handler: Callback) -> Self
+    where
+        Callback: FnMut(WebsocketEvent) -> Fallible<()>
+    {
+        Self::with_signer(hmac_signer(api_key, api_secret), handler)
+    }
+
+    pub fn with_signer<Callback: 'static>(signer: Arc<dyn Signer>, handler: Callback) -> Self
+    where
+        Callback: FnMut(WebsocketEvent) -> Fallible<()>
+    {
+        Self::build(Some(signer), handler)
+    }
+
+    // Authenticate with the client's signer and sign with its exchange clock.
+    pub fn from_client<Callback: 'static>(client: &HuobiFuture, handler: Callback) -> Self
+    where
+        Callback: FnMut(WebsocketEvent) -> Fallible<()>
+    {
+        let mut ws = Self::build(client.transport.signer(), handler);
+        ws.time_sync = client.time_sync();
+        ws
+    }
+
+    // Like `with_signer`, signing with a clock shared with a client.
+    pub fn with_time_sync<Callback: 'static>(signer: Arc<dyn Signer>, time_sync: TimeSync, handler: Callback) -> Self
+    where
+        Callback: FnMut(WebsocketEvent) -> Fallible<()>
+    {
+        let mut ws = Self::build(Some(signer), handler);
+        ws.time_sync = time_sync;
+        ws
+    }
+
+    fn build<Callback: 'static>(signer: Option<Arc<dyn Signer>>, handler: Callback) -> Self
     where
         Callback: FnMut(WebsocketEvent) -> Fallible<()>
     {
         Self {
-            credential: Some((api_key.into(), api_secret.into())),
+            signer,
             subscriptions: HashMap::new(),
             tokens: HashMap::new(),
             streams: StreamUnordered::new(),
             sinks: HashMap::new(),
             handler: Box::new(handler),
+            failure_handles: Vec::new(),
+            time_sync: TimeSync::new(),
         }
     }
 
+    // Sign with the exchange clock tracked by `HuobiFuture::time_sync`.
+    pub fn set_time_sync(&mut self, time_sync: TimeSync) {
+        self.time_sync = time_sync;
+    }
+
+    // Stop the given cancel-after keeper when this websocket's connection loop fails.
+    pub fn report_failures_to(&mut self, handle: KeeperHandle) {
+        self.failure_handles.push(handle);
+    }
+
     pub async fn subscribe(&mut self, subscription: Subscription) -> Fallible<()> {
         let end = match subscription {
             Subscription::Market => "/ws",
             Subscription::Account => "/notification",
             Subscription::Index => "/ws_index",
+            Subscription::CenterNotification => "/center-notification",
         };
 
         trace!("[Websocket] Subscribing to '{:?}'", subscription);
@@ -89,10 +157,10 @@ impl HuobiWebsocket {
     }
 
 
-    pub fn check_key(&self) -> Fallible<(&str, &str)> {
-        match self.credential.as_ref() {
+    pub fn check_key(&self) -> Fallible<Arc<dyn Signer>> {
+        match self.signer.as_ref() {
             None => Err(Error::NoApiKeySet.into()),
-            Some((k, s)) => Ok((k, s)),
+            Some(signer) => Ok(signer.clone()),
         }
     }
 
@@ -124,19 +192,43 @@ fn parse_message(msg: Message) -> Fallible<WebsocketEvent> {
     let bin = match msg {
         Message::Text(msg) => return Ok(WebsocketEvent::Text(msg)),
         Message::Binary(b) => b,
-        Message::Pong(b) => b,
-        Message::Ping(b) => b,
+        Message::Ping(_) => return Ok(WebsocketEvent::Ping),
+        Message::Pong(_) => return Ok(WebsocketEvent::Pong),
         Message::Close(..) => return Err(failure::format_err!("Socket closed")),
     };
 
     let mut d = GzDecoder::new(&*bin);
-    let mut s = String::new();
-    d.read_to_string(&mut s).unwrap();
+    let mut buf = Vec::new();
+    if let Err(e) = d.read_to_end(&mut buf) {
+        return Err(Error::WebsocketDecode {
+            kind: DecodeErrorKind::Gzip,
+            reason: e.to_string(),
+            raw: String::from_utf8_lossy(&bin).into_owned(),
+        }
+        .into());
+    }
+
+    let s = match String::from_utf8(buf) {
+        Ok(s) => s,
+        Err(e) => {
+            return Err(Error::WebsocketDecode {
+                kind: DecodeErrorKind::Utf8,
+                reason: e.to_string(),
+                raw: String::from_utf8_lossy(e.as_bytes()).into_owned(),
+            }
+            .into())
+        }
+    };
 
     trace!("Incoming websocket message {:?}", s);
-    
-    let message: WebsocketEvent = from_str(&s)?;
 
-    Ok(message)
+    // unknown but well-formed payloads fall through to `WebsocketEvent::Raw`
+    decode_event(&s).map_err(|e| {
+        Error::WebsocketDecode {
+            kind: DecodeErrorKind::Json,
+            reason: e.to_string(),
+            raw: s,
+        }
+        .into()
+    })
 }
-
@@ -0,0 +1,162 @@
+use crate::error::Error;
+use failure::Fallible;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+// Huobi accepts client order ids in [1, 9223372036854775807].
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct ClientOrderId(pub u64);
+
+impl ClientOrderId {
+    // Next id from the process-wide generator.
+    pub fn next() -> Self {
+        DEFAULT_GENERATOR.next()
+    }
+
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for ClientOrderId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<u64> for ClientOrderId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+impl From<u32> for ClientOrderId {
+    fn from(id: u32) -> Self {
+        Self(u64::from(id))
+    }
+}
+
+impl From<ClientOrderId> for u64 {
+    fn from(id: ClientOrderId) -> Self {
+        id.0
+    }
+}
+
+static DEFAULT_GENERATOR: ClientOrderIdGenerator = ClientOrderIdGenerator::with_node(0);
+
+// Ids per node per millisecond.
+const SEQUENCE_SPAN: u64 = 10_000;
+
+// Ids are `unix_ms * 10^6 + node * 10^4 + sequence`, strictly increasing within a
+// generator. Give every process that trades on the same account its own `node`
+// (0..=99) so ids stay unique across processes and restarts.
+#[derive(Debug)]
+pub struct ClientOrderIdGenerator {
+    node: u64,
+    last: AtomicU64,
+}
+
+impl ClientOrderIdGenerator {
+    pub fn new(node: u8) -> Fallible<Self> {
+        if node >= 100 {
+            return Err(Error::InvalidIdNode { node }.into());
+        }
+        Ok(Self::with_node(node))
+    }
+
+    const fn with_node(node: u8) -> Self {
+        Self {
+            node: node as u64,
+            last: AtomicU64::new(0),
+        }
+    }
+
+    // Waits for the next millisecond once this one's sequence is used up.
+    pub fn next(&self) -> ClientOrderId {
+        loop {
+            let ms = chrono::Utc::now().timestamp_millis() as u64;
+            if let Some(id) = self.next_at(ms) {
+                return id;
+            }
+            std::thread::yield_now();
+        }
+    }
+
+    // `None` when no id is left for `ms`, e.g. after 10^4 ids in that millisecond.
+    fn next_at(&self, ms: u64) -> Option<ClientOrderId> {
+        let base = ms * 1_000_000 + self.node * 10_000;
+
+        let mut last = self.last.load(Ordering::Relaxed);
+        loop {
+            // after a clock step back, keep counting in the millisecond of the last id
+            let (id, start) = if last >= base {
+                (last + 1, last - last % 1_000_000 + self.node * 10_000)
+            } else {
+                (base, base)
+            };
+            if id - start >= SEQUENCE_SPAN {
+                return None;
+            }
+            match self.last.compare_exchange_weak(last, id, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => return Some(ClientOrderId(id)),
+                Err(current) => last = current,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    const MS: u64 = 1_600_000_000_000;
+
+    #[test]
+    fn ids_strictly_increase() {
+        let generator = ClientOrderIdGenerator::new(3).unwrap();
+        let mut last = ClientOrderId(0);
+        for _ in 0..20_000 {
+            let id = generator.next();
+            assert!(id > last);
+            last = id;
+        }
+
+        // a clock step back keeps counting from the last id
+        let generator = ClientOrderIdGenerator::new(3).unwrap();
+        let first = generator.next_at(MS + 1).unwrap();
+        assert!(generator.next_at(MS).unwrap() > first);
+    }
+
+    #[test]
+    fn nodes_never_collide() {
+        let a = ClientOrderIdGenerator::new(1).unwrap();
+        let b = ClientOrderIdGenerator::new(2).unwrap();
+
+        let from_a: HashSet<_> = std::iter::from_fn(|| a.next_at(MS)).collect();
+        let from_b: HashSet<_> = std::iter::from_fn(|| b.next_at(MS)).collect();
+        assert_eq!(from_a.len(), SEQUENCE_SPAN as usize);
+        assert_eq!(from_b.len(), SEQUENCE_SPAN as usize);
+        assert!(from_a.is_disjoint(&from_b));
+    }
+
+    #[test]
+    fn exhausted_sequence_waits_for_the_next_millisecond() {
+        let generator = ClientOrderIdGenerator::new(99).unwrap();
+        for _ in 0..SEQUENCE_SPAN {
+            generator.next_at(MS).unwrap();
+        }
+        assert!(generator.next_at(MS).is_none());
+        assert_eq!(generator.next_at(MS + 1), Some(ClientOrderId((MS + 1) * 1_000_000 + 990_000)));
+    }
+
+    #[test]
+    fn node_must_fit_two_digits() {
+        assert!(ClientOrderIdGenerator::new(99).is_ok());
+        assert!(ClientOrderIdGenerator::new(100).is_err());
+    }
+}
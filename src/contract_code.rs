@@ -0,0 +1,121 @@
+use crate::error::Error;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::{borrow::Borrow, fmt, str::FromStr};
+
+// Delivery contract code such as "BTC200925": the symbol followed by the delivery
+// date as yyMMdd. Codes are uppercased however they are built, so lookups don't
+// depend on case; codes in an unexpected format still deserialize and simply yield
+// no delivery date.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(from = "String", into = "String")]
+pub struct ContractCode(String);
+
+impl ContractCode {
+    pub fn new(symbol: &str, delivery_date: NaiveDate) -> Self {
+        Self(format!("{}{}", symbol.to_uppercase(), delivery_date.format("%y%m%d")))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn symbol(&self) -> &str {
+        let end = self.0.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or_else(|| self.0.len());
+        &self.0[..end]
+    }
+
+    pub fn delivery_date(&self) -> Option<NaiveDate> {
+        let digits = &self.0[self.symbol().len()..];
+        if digits.len() != 6 {
+            return None;
+        }
+        NaiveDate::parse_from_str(digits, "%y%m%d").ok()
+    }
+}
+
+impl FromStr for ContractCode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = Self::from(s);
+        if code.symbol().is_empty() || code.delivery_date().is_none() {
+            return Err(Error::InvalidContractCode { code: s.to_string() });
+        }
+        Ok(code)
+    }
+}
+
+impl fmt::Display for ContractCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for ContractCode {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for ContractCode {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for ContractCode {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl From<ContractCode> for String {
+    fn from(code: ContractCode) -> Self {
+        code.0
+    }
+}
+
+impl From<ContractCode> for Option<String> {
+    fn from(code: ContractCode) -> Self {
+        Some(code.0)
+    }
+}
+
+impl From<String> for ContractCode {
+    fn from(mut code: String) -> Self {
+        code.make_ascii_uppercase();
+        Self(code)
+    }
+}
+
+impl From<&str> for ContractCode {
+    fn from(code: &str) -> Self {
+        Self(code.to_ascii_uppercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn case_is_normalized_however_built() {
+        let parsed: ContractCode = "btc200925".parse().unwrap();
+        let deserialized: ContractCode = serde_json::from_str("\"btc200925\"").unwrap();
+        assert_eq!(parsed.as_str(), "BTC200925");
+        assert_eq!(ContractCode::from("btc200925"), parsed);
+        assert_eq!(ContractCode::from("Btc200925".to_string()), parsed);
+        assert_eq!(deserialized, parsed);
+        assert_eq!(ContractCode::new("btc", NaiveDate::from_ymd(2020, 9, 25)), parsed);
+    }
+
+    #[test]
+    fn symbol_and_delivery_date() {
+        let code = ContractCode::from("BTC200925");
+        assert_eq!(code.symbol(), "BTC");
+        assert_eq!(code.delivery_date(), Some(NaiveDate::from_ymd(2020, 9, 25)));
+        assert_eq!(ContractCode::from("BTC-USD").delivery_date(), None);
+        assert!("BTC-USD".parse::<ContractCode>().is_err());
+    }
+}
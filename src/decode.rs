@@ -0,0 +1,219 @@
+use crate::models::*;
+use serde::{
+    de::{DeserializeOwned, IgnoredAny},
+    Deserialize,
+};
+use serde_json::{from_str, Result};
+use std::borrow::Cow;
+
+// Routing fields of a websocket frame; everything else is skipped without allocating.
+#[derive(Deserialize)]
+struct Envelope<'a> {
+    #[serde(borrow)]
+    ch: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    topic: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    op: Option<Cow<'a, str>>,
+    ping: Option<u64>,
+    #[serde(borrow)]
+    subbed: Option<Cow<'a, str>>,
+    // only its presence matters; any shape is skipped
+    status: Option<IgnoredAny>,
+}
+
+#[derive(Clone, Copy)]
+enum Route {
+    MarketPing,
+    SubStatus,
+    OpStatus,
+    IncrementalOrderBook,
+    OrderBook,
+    BBO,
+    Kline,
+    TradeDetail,
+    Basis,
+    Index,
+    Account,
+    Order,
+    MatchOrder,
+    Position,
+    Liquidation,
+    ContractInfo,
+    TriggerOrder,
+    TrackOrder,
+    ServiceHeartbeat,
+}
+
+// Decode a websocket frame by reading `ch`/`topic`/`op` first and deserializing straight
+// into the matching type. Frames that cannot be routed, or that do not fit the routed
+// type, fall back to the untagged `WebsocketEvent` representation.
+pub fn decode_event(text: &str) -> Result<WebsocketEvent> {
+    let route = from_str::<Envelope<'_>>(text).ok().and_then(|env| route(&env));
+
+    if let Some(route) = route {
+        if let Ok(event) = decode_routed(route, text) {
+            return Ok(event);
+        }
+    }
+
+    from_str(text)
+}
+
+fn route(env: &Envelope<'_>) -> Option<Route> {
+    if let Some(ch) = &env.ch {
+        return route_channel(ch);
+    }
+
+    if let Some(op) = &env.op {
+        return match op.as_ref() {
+            "notify" => env.topic.as_deref().and_then(route_topic),
+            _ => Some(Route::OpStatus),
+        };
+    }
+
+    if env.ping.is_some() {
+        return Some(Route::MarketPing);
+    }
+
+    if env.subbed.is_some() || env.status.is_some() {
+        return Some(Route::SubStatus);
+    }
+
+    None
+}
+
+// `market.$contract_code.$kind...`
+fn route_channel(ch: &str) -> Option<Route> {
+    let mut parts = ch.split('.');
+    if parts.next()? != "market" {
+        return None;
+    }
+    let _contract = parts.next()?;
+
+    match parts.next()? {
+        "depth" if ch.ends_with(".high_freq") => Some(Route::IncrementalOrderBook),
+        "depth" => Some(Route::OrderBook),
+        "bbo" => Some(Route::BBO),
+        "kline" => Some(Route::Kline),
+        "trade" => Some(Route::TradeDetail),
+        "basis" => Some(Route::Basis),
+        "index" => Some(Route::Index),
+        _ => None,
+    }
+}
+
+fn route_topic(topic: &str) -> Option<Route> {
+    let mut parts = topic.split('.');
+
+    match parts.next()? {
+        "orders" => Some(Route::Order),
+        "matchOrders" => Some(Route::MatchOrder),
+        "accounts" => Some(Route::Account),
+        "positions" => Some(Route::Position),
+        "trigger_order" => Some(Route::TriggerOrder),
+        "track_order" => Some(Route::TrackOrder),
+        "public" => match parts.nth(1)? {
+            "liquidation_orders" => Some(Route::Liquidation),
+            "contract_info" => Some(Route::ContractInfo),
+            "heartbeat" => Some(Route::ServiceHeartbeat),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn decode_routed(route: Route, text: &str) -> Result<WebsocketEvent> {
+    Ok(match route {
+        Route::MarketPing => WebsocketEvent::MarketPing(parse(text)?),
+        Route::SubStatus => WebsocketEvent::SubStatus(parse(text)?),
+        Route::OpStatus => WebsocketEvent::OpStatus(parse(text)?),
+        Route::IncrementalOrderBook => WebsocketEvent::IncrementalOrderBook(parse(text)?),
+        Route::OrderBook => WebsocketEvent::OrderBook(parse(text)?),
+        Route::BBO => WebsocketEvent::BBO(parse(text)?),
+        Route::Kline => WebsocketEvent::Kline(parse(text)?),
+        Route::TradeDetail => WebsocketEvent::TradeDetail(parse(text)?),
+        Route::Basis => WebsocketEvent::Basis(parse(text)?),
+        Route::Index => WebsocketEvent::Index(parse(text)?),
+        Route::Account => WebsocketEvent::Account(parse(text)?),
+        Route::Order => WebsocketEvent::Order(parse(text)?),
+        Route::MatchOrder => WebsocketEvent::MatchOrder(parse(text)?),
+        Route::Position => WebsocketEvent::Position(parse(text)?),
+        Route::Liquidation => WebsocketEvent::Liquidation(parse(text)?),
+        Route::ContractInfo => WebsocketEvent::ContractInfo(parse(text)?),
+        Route::TriggerOrder => WebsocketEvent::TriggerOrder(parse(text)?),
+        Route::TrackOrder => WebsocketEvent::TrackOrder(parse(text)?),
+        Route::ServiceHeartbeat => WebsocketEvent::ServiceHeartbeat(parse(text)?),
+    })
+}
+
+fn parse<T: DeserializeOwned>(text: &str) -> Result<T> {
+    from_str(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAMES: &str = include_str!("../fixtures/ws_frames.txt");
+    const SESSION: &str = include_str!("../fixtures/ws_session.txt");
+
+    fn variant(event: &WebsocketEvent) -> String {
+        format!("{:?}", event).split('(').next().unwrap_or_default().to_string()
+    }
+
+    #[test]
+    fn fixture_frames_decode_without_fallback() {
+        let expected = [
+            "MarketPing",
+            "SubStatus",
+            "OpStatus",
+            "OpStatus",
+            "OrderBook",
+            "IncrementalOrderBook",
+            "BBO",
+            "TradeDetail",
+            "Kline",
+            "Index",
+            "Basis",
+            "Account",
+            "Order",
+            "MatchOrder",
+            "Position",
+            "Liquidation",
+            "ContractInfo",
+            "TriggerOrder",
+            "TrackOrder",
+            "ServiceHeartbeat",
+        ];
+        let frames: Vec<_> = FRAMES.lines().filter(|l| !l.trim().is_empty()).collect();
+        assert_eq!(frames.len(), expected.len());
+
+        for (frame, expected) in frames.iter().zip(&expected) {
+            let route = route(&from_str::<Envelope<'_>>(frame).unwrap()).expect(frame);
+            let event = decode_routed(route, frame).unwrap_or_else(|e| panic!("{}: {}", e, frame));
+            assert_eq!(&variant(&event), expected);
+
+            let untagged: WebsocketEvent = from_str(frame).unwrap();
+            assert_eq!(format!("{:?}", untagged), format!("{:?}", event));
+        }
+    }
+
+    #[test]
+    fn session_frames_decode_like_the_untagged_enum() {
+        for frame in SESSION.lines().filter(|l| !l.trim().is_empty()) {
+            let route = route(&from_str::<Envelope<'_>>(frame).unwrap()).expect(frame);
+            let event = decode_routed(route, frame).unwrap_or_else(|e| panic!("{}: {}", e, frame));
+            let untagged: WebsocketEvent = from_str(frame).unwrap();
+            assert_eq!(format!("{:?}", untagged), format!("{:?}", event));
+        }
+    }
+
+    #[test]
+    fn status_of_any_shape_routes_to_sub_status() {
+        let frame = r#"{"id":"id2","status":"error","err-code":"bad-request","err-msg":"invalid topic","ts":1}"#;
+        assert!(matches!(route(&from_str::<Envelope<'_>>(frame).unwrap()), Some(Route::SubStatus)));
+        let frame = r#"{"status":{"code":1}}"#;
+        assert!(matches!(route(&from_str::<Envelope<'_>>(frame).unwrap()), Some(Route::SubStatus)));
+    }
+}
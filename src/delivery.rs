@@ -0,0 +1,308 @@
+use crate::{client::HuobiFuture, models::*};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveDate, TimeZone, Utc, Weekday};
+use failure::Fallible;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+// Contracts deliver, and all contracts settle weekly, on Fridays at 16:00 HKT.
+const SETTLEMENT_HOUR_UTC: u32 = 8;
+// contract_status while settling / delivering
+const STATUS_SETTLING: u32 = 5;
+const STATUS_DELIVERING: u32 = 6;
+
+#[derive(Debug, Clone)]
+pub struct ContractEntry {
+    pub symbol: String,
+    pub contract_code: ContractCode,
+    pub contract_type: String,
+    pub delivery_date: NaiveDate,
+    pub contract_status: u32,
+}
+
+impl ContractEntry {
+    pub fn delivery_time(&self) -> DateTime<Utc> {
+        settlement_time(self.delivery_date)
+    }
+}
+
+#[derive(Debug)]
+struct CalendarState {
+    contracts: HashMap<String, ContractEntry>,
+    before: ChronoDuration,
+    after: ChronoDuration,
+}
+
+// Delivery schedule of the listed contracts, fed by `get_contract_info` and the
+// `public.$symbol.contract_info` topic. Resolves `BTC_CW`-style aliases to contract
+// codes and tells whether the weekly settlement window is open. Clones share state.
+#[derive(Debug, Clone)]
+pub struct DeliveryCalendar {
+    state: Arc<Mutex<CalendarState>>,
+}
+
+impl Default for DeliveryCalendar {
+    // Trading stops for about ten minutes from 16:00 HKT.
+    fn default() -> Self {
+        Self::new(Duration::from_secs(0), Duration::from_secs(10 * 60))
+    }
+}
+
+impl DeliveryCalendar {
+    // `before` / `after` extend the blocked window around each settlement time.
+    pub fn new(before: Duration, after: Duration) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CalendarState {
+                contracts: HashMap::new(),
+                before: ChronoDuration::from_std(before).unwrap_or_else(|_| ChronoDuration::zero()),
+                after: ChronoDuration::from_std(after).unwrap_or_else(|_| ChronoDuration::zero()),
+            })),
+        }
+    }
+
+    pub fn set_contracts(&self, contracts: &[Symbol]) {
+        let mut state = self.state.lock().unwrap();
+        for c in contracts {
+            state.contracts.insert(
+                c.contract_code.to_string(),
+                ContractEntry {
+                    symbol: c.symbol.clone(),
+                    contract_code: c.contract_code.clone(),
+                    contract_type: c.contract_type.clone(),
+                    delivery_date: c.delivery_date,
+                    contract_status: c.contract_status,
+                },
+            );
+        }
+    }
+
+    pub fn apply_contract_info(&self, contracts: &[ContractInfo]) {
+        let mut state = self.state.lock().unwrap();
+        for c in contracts {
+            state.contracts.insert(
+                c.contract_code.to_string(),
+                ContractEntry {
+                    symbol: c.symbol.clone(),
+                    contract_code: c.contract_code.clone(),
+                    contract_type: c.contract_type.clone(),
+                    delivery_date: c.delivery_date,
+                    contract_status: c.contract_status,
+                },
+            );
+        }
+    }
+
+    pub fn handle_event(&self, event: &WebsocketEvent) {
+        if let WebsocketEvent::ContractInfo(msg) = event {
+            self.apply_contract_info(&msg.data);
+        }
+    }
+
+    // Replace a symbol's contracts with the exchange's current listing.
+    pub async fn refresh(&self, client: &HuobiFuture, symbol: &str) -> Fallible<()> {
+        let resp = client.get_contract_info(symbol.to_string(), None, None)?.await?;
+        self.replace_contracts(symbol, &resp.data.unwrap_or_default());
+        Ok(())
+    }
+
+    fn replace_contracts(&self, symbol: &str, contracts: &[Symbol]) {
+        self.state.lock().unwrap().contracts.retain(|_, c| c.symbol != symbol);
+        self.set_contracts(contracts);
+    }
+
+    // A listed contract has passed its delivery time, so contract types are stale.
+    pub fn needs_refresh(&self, now: DateTime<Utc>) -> bool {
+        self.state.lock().unwrap().contracts.values().any(|c| c.delivery_time() <= now)
+    }
+
+    // As `needs_refresh`, for one symbol's contracts.
+    pub fn needs_refresh_for(&self, symbol: &str, now: DateTime<Utc>) -> bool {
+        let state = self.state.lock().unwrap();
+        state.contracts.values().any(|c| c.symbol == symbol && c.delivery_time() <= now)
+    }
+
+    // Contracts of a symbol that have not delivered yet, by delivery date.
+    pub fn contracts(&self, symbol: &str, now: DateTime<Utc>) -> Vec<ContractEntry> {
+        let state = self.state.lock().unwrap();
+        let mut contracts: Vec<_> = state
+            .contracts
+            .values()
+            .filter(|c| c.symbol == symbol && c.delivery_time() > now)
+            .cloned()
+            .collect();
+        contracts.sort_by_key(|c| c.delivery_date);
+        contracts
+    }
+
+    // Contract code currently behind `symbol` + `contract_type` ("this_week", "quarter", ...).
+    // `None` once one of the symbol's contracts has delivered: the remaining contracts keep
+    // their old types until the next refresh relabels them.
+    pub fn resolve(&self, symbol: &str, contract_type: &str, now: DateTime<Utc>) -> Option<ContractCode> {
+        if self.needs_refresh_for(symbol, now) {
+            return None;
+        }
+        self.contracts(symbol, now)
+            .into_iter()
+            .find(|c| c.contract_type == contract_type)
+            .map(|c| c.contract_code)
+    }
+
+    // Resolve an alias such as "BTC_CW", "BTC_NW", "BTC_CQ" or "BTC_NQ".
+    pub fn resolve_alias(&self, alias: &str, now: DateTime<Utc>) -> Option<ContractCode> {
+        let mut parts = alias.rsplitn(2, '_');
+        let contract_type = match parts.next()? {
+            "CW" => "this_week",
+            "NW" => "next_week",
+            "CQ" => "quarter",
+            "NQ" => "next_quarter",
+            _ => return None,
+        };
+        self.resolve(parts.next()?, contract_type, now)
+    }
+
+    // The next contract of the same symbol to deliver after `contract_code`.
+    pub fn next_contract(&self, contract_code: &ContractCode, now: DateTime<Utc>) -> Option<ContractCode> {
+        let delivery = contract_code.delivery_date()?;
+        self.contracts(contract_code.symbol(), now)
+            .into_iter()
+            .find(|c| c.delivery_date > delivery)
+            .map(|c| c.contract_code)
+    }
+
+    // End of the blocked window when `now` falls inside one: the weekly settlement, or a
+    // contract of `symbol` the exchange reports as settling or delivering. Without a
+    // symbol, any listed contract counts.
+    pub fn settlement_window(&self, symbol: Option<&str>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let state = self.state.lock().unwrap();
+
+        let settlement = next_settlement(now - state.after);
+        if now >= settlement - state.before && now < settlement + state.after {
+            return Some(settlement + state.after);
+        }
+        let settling = state.contracts.values().any(|c| {
+            symbol.map_or(true, |s| c.symbol.eq_ignore_ascii_case(s))
+                && (c.contract_status == STATUS_SETTLING || c.contract_status == STATUS_DELIVERING)
+                // a delivered contract is never updated again, so its last status is stale
+                && now < c.delivery_time() + state.after
+        });
+        if settling {
+            return Some(now + state.after);
+        }
+        None
+    }
+}
+
+pub fn settlement_time(date: NaiveDate) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&date.and_hms(SETTLEMENT_HOUR_UTC, 0, 0))
+}
+
+// First Friday settlement at or after `now`.
+pub fn next_settlement(now: DateTime<Utc>) -> DateTime<Utc> {
+    let today = now.date().naive_utc();
+    let days = (7 + Weekday::Fri.num_days_from_monday() as i64 - today.weekday().num_days_from_monday() as i64) % 7;
+    let candidate = settlement_time(today + ChronoDuration::days(days));
+    if candidate >= now {
+        candidate
+    } else {
+        candidate + ChronoDuration::days(7)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn contract(code: &str, contract_type: &str, delivery: &str) -> Symbol {
+        with_status(code, contract_type, delivery, 1)
+    }
+
+    fn with_status(code: &str, contract_type: &str, delivery: &str, status: u32) -> Symbol {
+        let symbol: ContractCode = code.into();
+        serde_json::from_value(json!({
+            "symbol": symbol.symbol(), "contract_code": code, "contract_type": contract_type,
+            "contract_size": 100.0, "price_tick": 0.01, "delivery_date": delivery, "create_date": "20200612",
+            "contract_status": status
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn aliases_stop_resolving_after_a_delivery() {
+        let calendar = DeliveryCalendar::default();
+        calendar.set_contracts(&[
+            contract("BTC201030", "this_week", "20201030"),
+            contract("BTC201106", "next_week", "20201106"),
+            contract("BTC201225", "quarter", "20201225"),
+        ]);
+
+        let before = Utc.ymd(2020, 10, 30).and_hms(7, 0, 0);
+        assert_eq!(calendar.resolve_alias("BTC_CW", before), Some("BTC201030".into()));
+        assert_eq!(calendar.resolve_alias("BTC_NW", before), Some("BTC201106".into()));
+        assert!(!calendar.needs_refresh(before));
+
+        // BTC201106 is now the current week but still labelled next_week
+        let after = Utc.ymd(2020, 10, 30).and_hms(9, 0, 0);
+        assert!(calendar.needs_refresh(after));
+        assert_eq!(calendar.resolve_alias("BTC_NW", after), None);
+        assert_eq!(calendar.resolve_alias("BTC_CQ", after), None);
+        assert_eq!(calendar.next_contract(&"BTC201030".into(), after), Some("BTC201106".into()));
+    }
+
+    #[test]
+    fn aliases_roll_over_after_a_refresh() {
+        let calendar = DeliveryCalendar::default();
+        calendar.set_contracts(&[
+            contract("BTC201030", "this_week", "20201030"),
+            contract("BTC201106", "next_week", "20201106"),
+            contract("ETH201030", "this_week", "20201030"),
+        ]);
+
+        let after = Utc.ymd(2020, 10, 30).and_hms(9, 0, 0);
+        assert!(calendar.needs_refresh_for("BTC", after));
+
+        // the listing after delivery relabels the remaining contracts
+        calendar.replace_contracts("BTC", &[
+            contract("BTC201106", "this_week", "20201106"),
+            contract("BTC201113", "next_week", "20201113"),
+        ]);
+        assert!(!calendar.needs_refresh_for("BTC", after));
+        assert!(calendar.needs_refresh_for("ETH", after));
+        assert_eq!(calendar.resolve_alias("BTC_CW", after), Some("BTC201106".into()));
+        assert_eq!(calendar.resolve_alias("BTC_NW", after), Some("BTC201113".into()));
+        assert_eq!(calendar.resolve_alias("ETH_CW", after), None);
+    }
+
+    #[test]
+    fn settling_contracts_block_only_their_symbol() {
+        let calendar = DeliveryCalendar::default();
+        calendar.set_contracts(&[
+            contract("BTC201106", "this_week", "20201106"),
+            with_status("ETH201106", "this_week", "20201106", STATUS_SETTLING),
+        ]);
+
+        // a Tuesday, far from the weekly settlement
+        let now = Utc.ymd(2020, 11, 3).and_hms(3, 0, 0);
+        assert_eq!(calendar.settlement_window(Some("BTC"), now), None);
+        assert!(calendar.settlement_window(Some("eth"), now).is_some());
+        assert!(calendar.settlement_window(None, now).is_some());
+    }
+
+    #[test]
+    fn stale_delivering_status_does_not_block() {
+        let calendar = DeliveryCalendar::default();
+        calendar.set_contracts(&[
+            with_status("BTC201030", "this_week", "20201030", STATUS_DELIVERING),
+            contract("BTC201106", "next_week", "20201106"),
+        ]);
+
+        let delivering = Utc.ymd(2020, 10, 30).and_hms(8, 5, 0);
+        assert!(calendar.settlement_window(Some("BTC"), delivering).is_some());
+
+        // days after delivery the contract still reports delivering
+        let later = Utc.ymd(2020, 11, 3).and_hms(3, 0, 0);
+        assert_eq!(calendar.settlement_window(Some("BTC"), later), None);
+    }
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use snafu::*;
 
@@ -14,6 +15,39 @@ pub enum Error {
     NoApiKeySet,
     #[snafu(display("No stream is subscribed"))]
     NoStreamSubscribed,
+    #[snafu(display("Websocket frame decode error ({:?}): {}", kind, reason))]
+    WebsocketDecode {
+        kind: DecodeErrorKind,
+        reason: String,
+        // decompressed frame text, lossy when the payload was not valid UTF-8
+        raw: String,
+    },
+    #[snafu(display("Service {} is under maintenance", service))]
+    Maintenance {
+        service: String,
+        estimated_recovery_time: Option<u64>,
+    },
+    #[snafu(display("Pre-trade check rejected order on {}: {}", contract, reason))]
+    PreTradeRejected { contract: String, reason: String },
+    #[snafu(display("Cancellation of order {} was not confirmed", order_id))]
+    CancelNotConfirmed { order_id: u64 },
+    #[snafu(display("Invalid contract code: {}", code))]
+    InvalidContractCode { code: String },
+    #[snafu(display("Order placement blocked by settlement until {}", until))]
+    SettlementWindow { until: DateTime<Utc> },
+    #[snafu(display("Risk monitor handlers failed: {}", reasons.join("; ")))]
+    RiskHandlers { reasons: Vec<String> },
+    #[snafu(display("Client order id node {} is out of range 0..=99", node))]
+    InvalidIdNode { node: u8 },
+    #[snafu(display("Invalid cancel-after keeper settings: {}", reason))]
+    InvalidCancelAfter { reason: String },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeErrorKind {
+    Gzip,
+    Utf8,
+    Json,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -0,0 +1,233 @@
+use crate::models::*;
+
+// Coin-margined (inverse) contract math. Contracts have a face value of `contract_size`
+// USD, so values, margins and PnL are denominated in the coin.
+
+// Value in coin of `volume` contracts at `price`.
+pub fn position_value(volume: f64, contract_size: f64, price: f64) -> f64 {
+    if price <= 0.0 {
+        return 0.0;
+    }
+    volume * contract_size / price
+}
+
+pub fn initial_margin(volume: f64, contract_size: f64, price: f64, lever_rate: u32) -> f64 {
+    if lever_rate == 0 {
+        return 0.0;
+    }
+    position_value(volume, contract_size, price) / f64::from(lever_rate)
+}
+
+// The account is liquidated once risk rate, margin_balance / position_margin - adjust_factor,
+// reaches zero, i.e. when margin balance falls to position_margin * adjust_factor.
+pub fn maintenance_margin(volume: f64, contract_size: f64, price: f64, lever_rate: u32, adjust_factor: f64) -> f64 {
+    initial_margin(volume, contract_size, price, lever_rate) * adjust_factor
+}
+
+pub fn risk_rate(margin_balance: f64, position_margin: f64, adjust_factor: f64) -> Option<f64> {
+    if position_margin <= 0.0 {
+        return None;
+    }
+    Some(margin_balance / position_margin - adjust_factor)
+}
+
+// PnL in coin when `volume` contracts opened at `entry` are valued at `exit`.
+pub fn pnl(direction: &str, volume: f64, contract_size: f64, entry: f64, exit: f64) -> f64 {
+    if entry <= 0.0 || exit <= 0.0 {
+        return 0.0;
+    }
+    let pnl = volume * contract_size * (1.0 / entry - 1.0 / exit);
+    if direction == "buy" { pnl } else { -pnl }
+}
+
+// Average entry of two fills; inverse contracts average prices harmonically.
+pub fn average_entry(volume: f64, entry: f64, fill_volume: f64, fill_price: f64) -> f64 {
+    let total = volume + fill_volume;
+    if total <= 0.0 || entry <= 0.0 || fill_price <= 0.0 {
+        return if volume > 0.0 { entry } else { fill_price };
+    }
+    total / (volume / entry + fill_volume / fill_price)
+}
+
+// Estimated liquidation price of a single position, given the symbol's static margin
+// (margin balance excluding unrealized PnL). `None` when the margin cannot be exhausted,
+// e.g. an over-collateralized short.
+pub fn liquidation_price(
+    direction: &str,
+    volume: f64,
+    contract_size: f64,
+    entry: f64,
+    lever_rate: u32,
+    margin_static: f64,
+    adjust_factor: f64,
+) -> Option<f64> {
+    if volume <= 0.0 || entry <= 0.0 || lever_rate == 0 {
+        return None;
+    }
+    let face = volume * contract_size;
+    let k = adjust_factor / f64::from(lever_rate);
+
+    // solve margin_static + pnl(p) = position_margin(p) * adjust_factor for p
+    let (num, den) = if direction == "buy" {
+        (face * (1.0 + k), margin_static + face / entry)
+    } else {
+        (face * (1.0 - k), face / entry - margin_static)
+    };
+    if num <= 0.0 || den <= 0.0 {
+        return None;
+    }
+    Some(num / den)
+}
+
+// Fee amount in coin, with `fee_rate` as a fraction of the traded value.
+pub fn fee(volume: f64, contract_size: f64, price: f64, fee_rate: f64) -> f64 {
+    position_value(volume, contract_size, price) * fee_rate
+}
+
+impl Position {
+    pub fn value(&self, contract: &Symbol, price: f64) -> f64 {
+        position_value(self.volume, contract.contract_size, price)
+    }
+
+    pub fn unrealized_pnl(&self, contract: &Symbol, mark_price: f64) -> f64 {
+        pnl(&self.direction, self.volume, contract.contract_size, self.cost_hold, mark_price)
+    }
+
+    pub fn initial_margin(&self, contract: &Symbol, price: f64) -> f64 {
+        initial_margin(self.volume, contract.contract_size, price, self.lever_rate)
+    }
+
+    pub fn liquidation_price(&self, contract: &Symbol, margin_static: f64, adjust_factor: f64) -> Option<f64> {
+        liquidation_price(
+            &self.direction,
+            self.volume,
+            contract.contract_size,
+            self.cost_hold,
+            self.lever_rate,
+            margin_static,
+            adjust_factor,
+        )
+    }
+}
+
+impl TradeItem {
+    // Realized PnL of this fill closing a position opened at `entry`.
+    pub fn realized_pnl(&self, contract: &Symbol, direction: &str, entry: f64) -> f64 {
+        pnl(direction, self.trade_volume, contract.contract_size, entry, self.trade_price)
+    }
+
+    // Expected fee from the account's rates; `offset` is "open" or "close".
+    pub fn expected_fee(&self, contract: &Symbol, fees: &ContractFee, offset: &str) -> f64 {
+        let rate = match (offset, self.role.as_str()) {
+            ("open", "maker") | ("open", "Maker") => fees.open_maker_fee,
+            ("open", _) => fees.open_taker_fee,
+            (_, "maker") | (_, "Maker") => fees.close_maker_fee,
+            _ => fees.close_taker_fee,
+        };
+        fee(self.trade_volume, contract.contract_size, self.trade_price, rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    fn btc_quarter() -> Symbol {
+        serde_json::from_str(
+            r#"{"symbol":"BTC","contract_code":"BTC200925","contract_type":"quarter","contract_size":100,
+                "price_tick":0.01,"delivery_date":"20200925","create_date":"20200612","contract_status":1}"#,
+        )
+        .unwrap()
+    }
+
+    fn long_position() -> Position {
+        serde_json::from_str(
+            r#"{"symbol":"BTC","contract_code":"BTC200925","contract_type":"quarter","volume":10,"available":10,
+                "frozen":0,"cost_open":8000,"cost_hold":8000,"profit_unreal":0,"profit_rate":0,"profit":0,
+                "position_margin":0.0125,"lever_rate":10,"direction":"buy","last_price":8000}"#,
+        )
+        .unwrap()
+    }
+
+    // documented example: 10 contracts of 100 USD, long from 8000 to 9000
+    #[test]
+    fn documented_long_pnl() {
+        let pnl = pnl("buy", 10.0, 100.0, 8000.0, 9000.0);
+        assert!((pnl - 0.013889).abs() < 1e-6);
+        assert!(close(long_position().unrealized_pnl(&btc_quarter(), 9000.0), pnl));
+    }
+
+    #[test]
+    fn short_pnl_mirrors_long() {
+        assert!(close(pnl("sell", 10.0, 100.0, 8000.0, 9000.0), -pnl("buy", 10.0, 100.0, 8000.0, 9000.0)));
+    }
+
+    #[test]
+    fn value_and_margins() {
+        assert!(close(position_value(10.0, 100.0, 8000.0), 0.125));
+        assert!(close(initial_margin(10.0, 100.0, 8000.0, 10), 0.0125));
+        assert!(close(maintenance_margin(10.0, 100.0, 8000.0, 10, 0.075), 0.0125 * 0.075));
+        assert!(close(long_position().value(&btc_quarter(), 8000.0), 0.125));
+        assert!(close(long_position().initial_margin(&btc_quarter(), 8000.0), 0.0125));
+        assert!(close(risk_rate(0.05, 0.0125, 0.075).unwrap(), 4.0 - 0.075));
+    }
+
+    #[test]
+    fn liquidation_price_exhausts_margin() {
+        for direction in &["buy", "sell"] {
+            let price = liquidation_price(direction, 10.0, 100.0, 8000.0, 20, 0.05, 0.1).unwrap();
+            let balance = 0.05 + pnl(direction, 10.0, 100.0, 8000.0, price);
+            let rate = risk_rate(balance, initial_margin(10.0, 100.0, price, 20), 0.1).unwrap();
+            assert!(rate.abs() < 1e-9, "{} risk rate {}", direction, rate);
+        }
+        let long = liquidation_price("buy", 10.0, 100.0, 8000.0, 20, 0.05, 0.1).unwrap();
+        assert!((long - 5742.857142857).abs() < 1e-6);
+        let short = liquidation_price("sell", 10.0, 100.0, 8000.0, 20, 0.05, 0.1).unwrap();
+        assert!((short - 13266.666666667).abs() < 1e-6);
+
+        // a short backed by more than its face value can't be liquidated
+        assert_eq!(liquidation_price("sell", 10.0, 100.0, 8000.0, 20, 0.2, 0.1), None);
+        assert!(long_position().liquidation_price(&btc_quarter(), 0.05, 0.1).is_some());
+    }
+
+    #[test]
+    fn fees_from_symbol_metadata() {
+        let fees: ContractFee = serde_json::from_str(
+            r#"{"symbol":"BTC","open_maker_fee":"0.0002","open_taker_fee":"0.0004","close_maker_fee":"0.0002",
+                "close_taker_fee":"0.0004","delivery_fee":"0.0005","fee_asset":"BTC"}"#,
+        )
+        .unwrap();
+        let trade: TradeItem = serde_json::from_str(
+            r#"{"trade_id":1,"id":"1-2-1","trade_price":8000,"trade_volume":10,"trade_fee":-0.00005,
+                "fee_asset":"BTC","role":"taker","created_at":1600000000000}"#,
+        )
+        .unwrap();
+
+        assert!(close(fee(10.0, 100.0, 8000.0, 0.0004), 0.00005));
+        assert!(close(trade.expected_fee(&btc_quarter(), &fees, "open"), 0.00005));
+        assert!(close(trade.realized_pnl(&btc_quarter(), "sell", 9000.0), pnl("sell", 10.0, 100.0, 9000.0, 8000.0)));
+    }
+
+    #[test]
+    fn harmonic_average_entry() {
+        assert!(close(average_entry(1.0, 8000.0, 1.0, 10000.0), 2.0 / (1.0 / 8000.0 + 1.0 / 10000.0)));
+        assert!(close(average_entry(0.0, 0.0, 2.0, 9000.0), 9000.0));
+    }
+
+    #[test]
+    fn zero_volume_and_price() {
+        assert!(close(position_value(10.0, 100.0, 0.0), 0.0));
+        assert!(close(position_value(0.0, 100.0, 8000.0), 0.0));
+        assert!(close(initial_margin(10.0, 100.0, 8000.0, 0), 0.0));
+        assert!(close(pnl("buy", 10.0, 100.0, 0.0, 9000.0), 0.0));
+        assert!(close(pnl("buy", 10.0, 100.0, 8000.0, 0.0), 0.0));
+        assert!(close(pnl("buy", 0.0, 100.0, 8000.0, 9000.0), 0.0));
+        assert_eq!(risk_rate(0.05, 0.0, 0.1), None);
+        assert_eq!(liquidation_price("buy", 0.0, 100.0, 8000.0, 20, 0.05, 0.1), None);
+        assert_eq!(liquidation_price("buy", 10.0, 100.0, 0.0, 20, 0.05, 0.1), None);
+    }
+}
@@ -0,0 +1,422 @@
+use crate::{client::HuobiFuture, models::*};
+use failure::Fallible;
+use std::{
+    collections::{HashSet, VecDeque},
+    time::Duration,
+};
+
+// Trade ids remembered for de-duplicating reconnect replays and backfills.
+const SEEN_TRADES: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BarSpec {
+    // fixed periods aligned to the epoch
+    Time(Duration),
+    // number of trades
+    Ticks(u64),
+    // contracts traded
+    Volume(f64),
+    // USD face value traded, i.e. contracts * contract_size
+    Dollar(f64),
+}
+
+// Builds `Kline` bars from `market.$contract.trade.detail` trades. As on the exchange,
+// `volume` counts contracts, `amount` is the turnover in coin, `count` the trades and
+// `mrid` the last trade id; `timestamp` is the bar start in seconds.
+//
+// Threshold bars close on the trade that reaches the threshold, without splitting it;
+// trades older than one already taken are dropped as late. Time bars close once a trade
+// at or past `period end + grace` arrives (or on `flush`); trades for an already closed
+// bar are dropped and counted in `late_trades`. Periods without trades produce flat
+// zero-volume bars, except across a gap marked with `mark_gap` after a reconnect, where
+// the missing trades are unknown. Live trades are held back from `mark_gap` until the
+// backfill is applied, so the missing trades are placed before anything after them.
+#[derive(Debug)]
+pub struct KlineAggregator {
+    channel: Option<String>,
+    spec: BarSpec,
+    contract_size: f64,
+    grace_ms: u64,
+    current: Option<Kline>,
+    // start of the open bar in ms, and progress towards a threshold bar
+    period_start: u64,
+    progress: f64,
+    // end of the last emitted time bar, latest trade time, trades of later time periods
+    emitted_until: u64,
+    watermark: u64,
+    pending: Vec<TradeDetailItem>,
+    last_close: Option<f64>,
+    // live trades held while a gap waits for its backfill
+    held: Option<Vec<TradeDetailItem>>,
+    // no flat bars up to the next trade
+    gap: bool,
+    late_trades: u64,
+    seen: HashSet<u64>,
+    seen_order: VecDeque<u64>,
+}
+
+impl KlineAggregator {
+    pub fn new(spec: BarSpec, contract_size: f64) -> Self {
+        Self {
+            channel: None,
+            spec,
+            contract_size,
+            grace_ms: 0,
+            current: None,
+            period_start: 0,
+            progress: 0.0,
+            emitted_until: 0,
+            watermark: 0,
+            pending: Vec::new(),
+            last_close: None,
+            held: None,
+            gap: false,
+            late_trades: 0,
+            seen: HashSet::new(),
+            seen_order: VecDeque::new(),
+        }
+    }
+
+    // Only take trades from this contract (e.g. "BTC_CQ") in `handle_event`.
+    #[must_use]
+    pub fn with_channel(mut self, contract: &str) -> Self {
+        self.channel = Some(format!("market.{}.trade.detail", contract));
+        self
+    }
+
+    // How long a time bar stays open for out-of-order trades after its period ends.
+    #[must_use]
+    pub fn with_grace(mut self, grace: Duration) -> Self {
+        self.grace_ms = grace.as_millis() as u64;
+        self
+    }
+
+    pub fn late_trades(&self) -> u64 {
+        self.late_trades
+    }
+
+    pub fn current(&self) -> Option<&Kline> {
+        self.current.as_ref()
+    }
+
+    // Call after a reconnect: trades between the last one seen and the next may be missing.
+    // Trades are held until `backfill` or `apply_backfill`.
+    pub fn mark_gap(&mut self) {
+        self.gap = true;
+        self.held.get_or_insert_with(Vec::new);
+    }
+
+    pub fn handle_event(&mut self, event: &WebsocketEvent) -> Vec<Kline> {
+        match event {
+            WebsocketEvent::TradeDetail(msg) => {
+                if self.channel.as_ref().map_or(true, |ch| *ch == msg.ch) {
+                    return self.push_all(&msg.tick.data);
+                }
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn push_all(&mut self, trades: &[TradeDetailItem]) -> Vec<Kline> {
+        let mut closed = Vec::new();
+        for t in trades {
+            closed.extend(self.push(t));
+        }
+        closed
+    }
+
+    // Fill a reconnect gap from `/market/history/trade`; already seen trades are skipped.
+    pub async fn backfill(&mut self, client: &HuobiFuture, contract: &str, size: u32) -> Fallible<Vec<Kline>> {
+        let history = client.get_history_trades(contract.to_string(), size)?.await?;
+        let trades = history.data.into_iter().flat_map(|h| h.data).collect();
+        Ok(self.apply_backfill(trades))
+    }
+
+    // Merge recovered trades with the live trades held since `mark_gap` and resume.
+    pub fn apply_backfill(&mut self, mut trades: Vec<TradeDetailItem>) -> Vec<Kline> {
+        // reaching back to a trade already seen means nothing in between is missing
+        if trades.iter().any(|t| self.seen.contains(&t.id)) {
+            self.gap = false;
+        }
+        trades.extend(self.held.take().unwrap_or_default());
+        trades.sort_by_key(|t| (t.ts, t.id));
+        self.push_all(&trades)
+    }
+
+    pub fn push(&mut self, trade: &TradeDetailItem) -> Vec<Kline> {
+        if let Some(held) = &mut self.held {
+            held.push(trade.clone());
+            return Vec::new();
+        }
+        if !self.remember(trade.id) {
+            return Vec::new();
+        }
+
+        match self.spec {
+            BarSpec::Time(period) => self.push_timed(trade, (period.as_millis() as u64).max(1)),
+            BarSpec::Ticks(n) => self.push_threshold(trade, 1.0, n as f64),
+            BarSpec::Volume(v) => self.push_threshold(trade, trade.amount, v),
+            BarSpec::Dollar(d) => self.push_threshold(trade, trade.amount * self.contract_size, d),
+        }
+    }
+
+    // Close every open bar regardless of thresholds or time, e.g. on shutdown. Trades
+    // held for a backfill are applied first.
+    pub fn flush(&mut self) -> Vec<Kline> {
+        let mut closed = match self.held {
+            Some(_) => self.apply_backfill(Vec::new()),
+            None => Vec::new(),
+        };
+        match self.spec {
+            BarSpec::Time(period) => {
+                let period_ms = (period.as_millis() as u64).max(1);
+                while self.current.is_some() {
+                    self.close_period(period_ms, &mut closed);
+                }
+            }
+            _ => closed.extend(self.close_current()),
+        }
+        closed
+    }
+
+    fn close_current(&mut self) -> Option<Kline> {
+        self.progress = 0.0;
+        let bar = self.current.take();
+        if let Some(bar) = &bar {
+            self.last_close = Some(bar.close);
+        }
+        bar
+    }
+
+    fn push_timed(&mut self, trade: &TradeDetailItem, period_ms: u64) -> Vec<Kline> {
+        let mut closed = Vec::new();
+        self.watermark = self.watermark.max(millis(trade));
+        self.place(trade.clone(), period_ms, &mut closed);
+
+        while self.current.is_some() && self.watermark >= self.period_start + period_ms + self.grace_ms {
+            self.close_period(period_ms, &mut closed);
+        }
+        closed
+    }
+
+    // Emit the open time bar, then start the next one from trades held back meanwhile.
+    fn close_period(&mut self, period_ms: u64, closed: &mut Vec<Kline>) {
+        self.emitted_until = self.period_start + period_ms;
+        closed.extend(self.close_current());
+
+        let mut pending = std::mem::take(&mut self.pending);
+        pending.sort_by_key(|t| (t.ts, t.id));
+        for t in pending {
+            self.place(t, period_ms, closed);
+        }
+    }
+
+    fn place(&mut self, trade: TradeDetailItem, period_ms: u64, closed: &mut Vec<Kline>) {
+        let ts = millis(&trade);
+        let start = ts - ts % period_ms;
+
+        if start < self.emitted_until {
+            // its bar has already been emitted
+            self.late_trades += 1;
+            return;
+        }
+        if self.current.is_none() {
+            // flat bars for quiet periods, unless trades may have been lost
+            if let (false, Some(close)) = (self.gap, self.last_close) {
+                let mut empty = self.emitted_until;
+                while empty < start {
+                    closed.push(flat_bar(empty, close));
+                    empty += period_ms;
+                }
+            }
+            self.gap = false;
+            self.period_start = start;
+            self.add(&trade);
+        } else if start == self.period_start {
+            self.add(&trade);
+        } else if start < self.period_start {
+            // falls in a quiet period already skipped over
+            self.late_trades += 1;
+        } else {
+            // a later period, held until the open bar closes
+            self.pending.push(trade);
+        }
+    }
+
+    fn push_threshold(&mut self, trade: &TradeDetailItem, size: f64, threshold: f64) -> Vec<Kline> {
+        let ts = millis(trade);
+        if ts < self.watermark {
+            // bars are cut in trade order, so an older trade can't be placed
+            self.late_trades += 1;
+            return Vec::new();
+        }
+        self.watermark = ts;
+
+        if self.current.is_none() {
+            self.period_start = ts;
+        }
+        self.add(trade);
+        self.progress += size;
+
+        if self.progress >= threshold {
+            return self.close_current().into_iter().collect();
+        }
+        Vec::new()
+    }
+
+    fn add(&mut self, trade: &TradeDetailItem) {
+        let coin = if trade.price > 0.0 { trade.amount * self.contract_size / trade.price } else { 0.0 };
+        let start = self.period_start / 1000;
+
+        let bar = self.current.get_or_insert_with(|| Kline {
+            timestamp: start,
+            volume: 0.0,
+            count: 0.0,
+            open: trade.price,
+            close: trade.price,
+            low: trade.price,
+            high: trade.price,
+            amount: 0.0,
+            mrid: None,
+        });
+        bar.volume += trade.amount;
+        bar.count += 1.0;
+        bar.amount += coin;
+        bar.high = bar.high.max(trade.price);
+        bar.low = bar.low.min(trade.price);
+        // late trades inside the bar update extremes but not the close
+        if bar.mrid.map_or(true, |id| trade.id > id) {
+            bar.close = trade.price;
+            bar.mrid = Some(trade.id);
+        }
+    }
+
+    fn remember(&mut self, id: u64) -> bool {
+        if !self.seen.insert(id) {
+            return false;
+        }
+        self.seen_order.push_back(id);
+        if self.seen_order.len() > SEEN_TRADES {
+            if let Some(old) = self.seen_order.pop_front() {
+                self.seen.remove(&old);
+            }
+        }
+        true
+    }
+}
+
+fn millis(trade: &TradeDetailItem) -> u64 {
+    trade.ts.timestamp_millis().max(0) as u64
+}
+
+fn flat_bar(start_ms: u64, close: f64) -> Kline {
+    Kline {
+        timestamp: start_ms / 1000,
+        volume: 0.0,
+        count: 0.0,
+        open: close,
+        close,
+        low: close,
+        high: close,
+        amount: 0.0,
+        mrid: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn trade(id: u64, ts: i64, price: f64, amount: f64) -> TradeDetailItem {
+        TradeDetailItem { amount, ts: Utc.timestamp_millis(ts), id, price, direction: "buy".into() }
+    }
+
+    fn minute_bars(grace_ms: u64) -> KlineAggregator {
+        KlineAggregator::new(BarSpec::Time(Duration::from_secs(60)), 100.0).with_grace(Duration::from_millis(grace_ms))
+    }
+
+    #[test]
+    fn late_trades_within_grace_join_their_bar() {
+        let mut agg = minute_bars(2_000);
+        assert!(agg.push(&trade(1, 10_000, 100.0, 1.0)).is_empty());
+        assert!(agg.push(&trade(3, 60_500, 102.0, 1.0)).is_empty());
+        // out of order but inside the grace period
+        assert!(agg.push(&trade(2, 59_000, 99.0, 2.0)).is_empty());
+
+        let closed = agg.push(&trade(4, 62_000, 103.0, 1.0));
+        assert_eq!(closed.len(), 1);
+        assert_eq!((closed[0].timestamp, closed[0].volume, closed[0].low), (0, 3.0, 99.0));
+        assert_eq!((closed[0].close, closed[0].mrid), (99.0, Some(2)));
+
+        // the first minute has been emitted
+        assert!(agg.push(&trade(5, 30_000, 98.0, 1.0)).is_empty());
+        assert_eq!(agg.late_trades(), 1);
+
+        let rest = agg.flush();
+        assert_eq!(rest.len(), 1);
+        assert_eq!((rest[0].timestamp, rest[0].volume, rest[0].open), (60, 2.0, 102.0));
+    }
+
+    #[test]
+    fn quiet_periods_produce_flat_bars() {
+        let mut agg = minute_bars(0);
+        agg.push(&trade(1, 10_000, 100.0, 1.0));
+        let closed = agg.push(&trade(2, 190_000, 101.0, 1.0));
+        let starts: Vec<_> = closed.iter().map(|k| (k.timestamp, k.volume)).collect();
+        assert_eq!(starts, vec![(0, 1.0), (60, 0.0), (120, 0.0)]);
+    }
+
+    #[test]
+    fn live_trades_wait_for_the_backfill() {
+        let mut agg = minute_bars(0);
+        agg.push(&trade(1, 10_000, 100.0, 1.0));
+        agg.mark_gap();
+
+        // live trades after the reconnect, two minutes on
+        assert!(agg.push(&trade(5, 130_000, 105.0, 1.0)).is_empty());
+        assert!(agg.current().map_or(false, |k| k.volume == 1.0));
+
+        // history overlaps the last seen trade, so the gap is fully recovered
+        let closed = agg.apply_backfill(vec![
+            trade(3, 70_000, 103.0, 2.0),
+            trade(1, 10_000, 100.0, 1.0),
+            trade(2, 50_000, 102.0, 1.0),
+            trade(4, 125_000, 104.0, 1.0),
+        ]);
+        let bars: Vec<_> = closed.iter().map(|k| (k.timestamp, k.volume, k.close)).collect();
+        assert_eq!(bars, vec![(0, 2.0, 102.0), (60, 2.0, 103.0)]);
+        assert_eq!(agg.late_trades(), 0);
+        assert_eq!(agg.current().map(|k| (k.timestamp, k.volume)), Some((120, 2.0)));
+    }
+
+    #[test]
+    fn no_flat_bars_across_an_unrecovered_gap() {
+        let mut agg = minute_bars(0);
+        agg.push(&trade(1, 10_000, 100.0, 1.0));
+        agg.mark_gap();
+        agg.push(&trade(9, 250_000, 105.0, 1.0));
+
+        // history doesn't reach back to trade 1
+        let closed = agg.apply_backfill(vec![trade(8, 200_000, 104.0, 1.0)]);
+        let bars: Vec<_> = closed.iter().map(|k| k.timestamp).collect();
+        assert_eq!(bars, vec![0, 180]);
+    }
+
+    #[test]
+    fn threshold_bars_drop_out_of_order_trades() {
+        let mut agg = KlineAggregator::new(BarSpec::Volume(3.0), 100.0);
+        agg.push(&trade(1, 1_000, 100.0, 1.0));
+        agg.mark_gap();
+        agg.push(&trade(4, 4_000, 103.0, 1.0));
+
+        let closed = agg.apply_backfill(vec![trade(2, 2_000, 101.0, 1.0), trade(3, 3_000, 102.0, 1.0)]);
+        assert_eq!(closed.len(), 1);
+        assert_eq!((closed[0].open, closed[0].close, closed[0].mrid), (100.0, 102.0, Some(3)));
+
+        assert!(agg.push(&trade(0, 500, 99.0, 5.0)).is_empty());
+        assert_eq!(agg.late_trades(), 1);
+        assert_eq!(agg.flush().pop().map(|k| (k.volume, k.open)), Some((1.0, 103.0)));
+    }
+}
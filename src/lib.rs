@@ -1,11 +1,43 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery)]
 #![allow(clippy::missing_errors_doc)]
 mod client;
+pub mod client_order_id;
+pub mod contract_code;
+pub mod decode;
+pub mod delivery;
 pub mod error;
+pub mod inverse;
+pub mod kline_aggregator;
 pub mod models;
+pub mod order_manager;
+pub mod position_book;
+pub mod pre_trade;
+pub mod rate_limit;
+pub mod risk_monitor;
+pub mod signer;
+pub mod status;
+pub mod time_sync;
 mod transport;
 
 pub use crate::models::*;
 pub use crate::error::*;
+pub use crate::client_order_id::ClientOrderIdGenerator;
+pub use crate::delivery::{ContractEntry, DeliveryCalendar};
+pub use crate::kline_aggregator::{BarSpec, KlineAggregator};
+pub use crate::order_manager::{OrderManager, OrderState, TrackedOrder};
+pub use crate::position_book::{BookPosition, PositionBook};
+pub use crate::pre_trade::{ContractLimits, PreTradeChecker, TickPolicy};
+pub use crate::rate_limit::RateLimiter;
+pub use crate::risk_monitor::{RiskAlert, RiskLevel, RiskLimit, RiskMetric, RiskMonitor, RiskThresholds};
+pub use crate::status::{StatusMonitor, SystemStatus};
+pub use crate::time_sync::TimeSync;
 
-pub use crate::client::{websocket::HuobiWebsocket, HuobiFuture};
+pub use crate::client::{
+    cancel_after::{CancelAfterKeeper, KeeperHandle},
+    clock::TimeSyncTask,
+    status::StatusPollTask,
+    websocket::HuobiWebsocket,
+    BATCH_ORDER_MAX,
+    CANCEL_ORDER_MAX,
+    HuobiFuture,
+};
@@ -6,6 +6,47 @@ use serde::{Deserialize, Deserializer, Serialize};
 use std::fmt::{self, Display};
 use std::str::FromStr;
 
+pub use crate::client_order_id::ClientOrderId;
+pub use crate::contract_code::ContractCode;
+use chrono::serde::ts_milliseconds;
+use chrono::{DateTime, NaiveDate, Utc};
+
+// Dates such as `delivery_date` arrive as "yyyyMMdd" strings.
+mod yyyymmdd {
+    use chrono::NaiveDate;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(date: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&date.format("%Y%m%d").to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDate, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        NaiveDate::parse_from_str(&s, "%Y%m%d").map_err(de::Error::custom)
+    }
+}
+
+// Millisecond timestamps where 0 (or null) means "not yet", e.g. `canceled_at`.
+mod opt_ts_milliseconds {
+    use chrono::{DateTime, TimeZone, Utc};
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(ts: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(ts.map_or(0, |ts| ts.timestamp_millis()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+        match Option::<i64>::deserialize(deserializer)?.filter(|ms| *ms > 0) {
+            Some(ms) => Utc
+                .timestamp_millis_opt(ms)
+                .single()
+                .map(Some)
+                .ok_or_else(|| de::Error::custom(format!("timestamp {} out of range", ms))),
+            None => Ok(None),
+        }
+    }
+}
+
 fn from_str<'de, T, D>(deserializer: D) -> Result<T, D::Error>
 where
     T: FromStr,
@@ -22,6 +63,7 @@ pub struct APIResponse<R> {
     pub ts: Option<u64>,
     pub data: Option<R>,
     pub tick: Option<R>,
+    pub ticks: Option<R>,
     pub ch: Option<String>,
     pub err_code: Option<ErrCodeEnum>,
     pub err_msg: Option<String>,
@@ -36,7 +78,8 @@ pub struct APIResponse<R> {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct WSMarketResponse<T> {
     pub ch: String,
-    pub ts: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
     pub tick: T,
 }
 
@@ -44,7 +87,8 @@ pub struct WSMarketResponse<T> {
 pub struct WSAccountResponse<T> {
     pub op: String,
     pub topic: String,
-    pub ts: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
     pub uid: Option<String>,
     pub event: String,
     pub data: T,
@@ -69,12 +113,14 @@ pub struct Page<T> {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Symbol {
     pub symbol: String,
-    pub contract_code: String,
+    pub contract_code: ContractCode,
     pub contract_type: String,
     pub contract_size: f64,
     pub price_tick: f64,
-    pub delivery_date: String,
-    pub create_date: String,
+    #[serde(with = "yyyymmdd")]
+    pub delivery_date: NaiveDate,
+    #[serde(with = "yyyymmdd")]
+    pub create_date: NaiveDate,
     pub contract_status: u32,
 }
 
@@ -99,7 +145,7 @@ pub struct AccountPosition {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Position {
     pub symbol: String,
-    pub contract_code: String,
+    pub contract_code: ContractCode,
     pub contract_type: String,
     pub volume: f64,
     pub available: f64,
@@ -136,7 +182,7 @@ pub struct Account {
 pub struct Order {
     pub symbol: String,
     pub contract_type: String,
-    pub contract_code: String,
+    pub contract_code: ContractCode,
     pub volume: f64,
     pub price: f64,
     pub order_price_type: String,
@@ -145,9 +191,11 @@ pub struct Order {
     pub lever_rate: u32,
     pub order_id: u64,
     pub order_id_str: String,
-    pub client_order_id: u64,
-    pub created_at: u64,
-    pub canceled_at: u64,
+    pub client_order_id: Option<ClientOrderId>,
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "opt_ts_milliseconds")]
+    pub canceled_at: Option<DateTime<Utc>>,
     pub trade_volume: u32,
     pub trade_turnover: f64,
     pub fee: f64,
@@ -155,16 +203,266 @@ pub struct Order {
     pub trade_avg_price: Option<f64>,
     pub margin_frozen: f64,
     pub profit: f64,
-    pub status: u32,
-    pub order_type: u32,
-    pub order_source: String,
-    pub liquidation_type: String,
+    pub status: OrderStatus,
+    pub order_type: OrderType,
+    pub order_source: OrderSource,
+    pub liquidation_type: LiquidationType,
+}
+
+// Order status codes. Unknown codes are kept rather than rejected.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(from = "u32", into = "u32")]
+pub enum OrderStatus {
+    Ready,
+    Submitting,
+    Submitted,
+    PartiallyFilled,
+    PartiallyCancelled,
+    Filled,
+    Cancelled,
+    Cancelling,
+    Unknown(u32),
+}
+
+impl OrderStatus {
+    // No further fills or state changes will follow.
+    pub fn is_terminal(self) -> bool {
+        matches!(self, Self::PartiallyCancelled | Self::Filled | Self::Cancelled)
+    }
+
+    // Resting or about to rest on the book.
+    pub fn is_open(self) -> bool {
+        matches!(self, Self::Ready | Self::Submitting | Self::Submitted | Self::PartiallyFilled | Self::Cancelling)
+    }
+
+    pub fn has_fills(self) -> bool {
+        matches!(self, Self::PartiallyFilled | Self::PartiallyCancelled | Self::Filled)
+    }
+}
+
+impl From<u32> for OrderStatus {
+    fn from(code: u32) -> Self {
+        match code {
+            1 => Self::Ready,
+            2 => Self::Submitting,
+            3 => Self::Submitted,
+            4 => Self::PartiallyFilled,
+            5 => Self::PartiallyCancelled,
+            6 => Self::Filled,
+            7 => Self::Cancelled,
+            11 => Self::Cancelling,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<OrderStatus> for u32 {
+    fn from(status: OrderStatus) -> Self {
+        match status {
+            OrderStatus::Ready => 1,
+            OrderStatus::Submitting => 2,
+            OrderStatus::Submitted => 3,
+            OrderStatus::PartiallyFilled => 4,
+            OrderStatus::PartiallyCancelled => 5,
+            OrderStatus::Filled => 6,
+            OrderStatus::Cancelled => 7,
+            OrderStatus::Cancelling => 11,
+            OrderStatus::Unknown(code) => code,
+        }
+    }
+}
+
+// Trigger (and TP/SL) order status codes
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(from = "u32", into = "u32")]
+pub enum TriggerOrderStatus {
+    Ready,
+    Submitting,
+    Submitted,
+    Placed,
+    Failed,
+    Cancelled,
+    Unknown(u32),
+}
+
+impl TriggerOrderStatus {
+    pub fn is_terminal(self) -> bool {
+        matches!(self, Self::Placed | Self::Failed | Self::Cancelled)
+    }
+}
+
+impl From<u32> for TriggerOrderStatus {
+    fn from(code: u32) -> Self {
+        match code {
+            1 => Self::Ready,
+            2 => Self::Submitting,
+            3 => Self::Submitted,
+            4 => Self::Placed,
+            5 => Self::Failed,
+            6 => Self::Cancelled,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<TriggerOrderStatus> for u32 {
+    fn from(status: TriggerOrderStatus) -> Self {
+        match status {
+            TriggerOrderStatus::Ready => 1,
+            TriggerOrderStatus::Submitting => 2,
+            TriggerOrderStatus::Submitted => 3,
+            TriggerOrderStatus::Placed => 4,
+            TriggerOrderStatus::Failed => 5,
+            TriggerOrderStatus::Cancelled => 6,
+            TriggerOrderStatus::Unknown(code) => code,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(from = "u32", into = "u32")]
+pub enum OrderType {
+    Quotation,
+    Cancel,
+    Liquidation,
+    Delivery,
+    Unknown(u32),
+}
+
+impl From<u32> for OrderType {
+    fn from(code: u32) -> Self {
+        match code {
+            1 => Self::Quotation,
+            2 => Self::Cancel,
+            3 => Self::Liquidation,
+            4 => Self::Delivery,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<OrderType> for u32 {
+    fn from(order_type: OrderType) -> Self {
+        match order_type {
+            OrderType::Quotation => 1,
+            OrderType::Cancel => 2,
+            OrderType::Liquidation => 3,
+            OrderType::Delivery => 4,
+            OrderType::Unknown(code) => code,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum OrderSource {
+    System,
+    Web,
+    Api,
+    Mobile,
+    Risk,
+    Settlement,
+    Ios,
+    Android,
+    Windows,
+    Mac,
+    Trigger,
+    Tpsl,
+    Unknown(String),
+}
+
+impl OrderSource {
+    // Placed by the exchange rather than by the account holder.
+    pub fn is_exchange_initiated(&self) -> bool {
+        matches!(self, Self::System | Self::Risk | Self::Settlement)
+    }
+}
+
+impl From<String> for OrderSource {
+    fn from(source: String) -> Self {
+        match source.as_str() {
+            "system" => Self::System,
+            "web" => Self::Web,
+            "api" => Self::Api,
+            "m" => Self::Mobile,
+            "risk" => Self::Risk,
+            "settlement" => Self::Settlement,
+            "ios" => Self::Ios,
+            "android" => Self::Android,
+            "windows" => Self::Windows,
+            "mac" => Self::Mac,
+            "trigger" => Self::Trigger,
+            "tpsl" => Self::Tpsl,
+            _ => Self::Unknown(source),
+        }
+    }
+}
+
+impl From<OrderSource> for String {
+    fn from(source: OrderSource) -> Self {
+        match source {
+            OrderSource::System => "system".into(),
+            OrderSource::Web => "web".into(),
+            OrderSource::Api => "api".into(),
+            OrderSource::Mobile => "m".into(),
+            OrderSource::Risk => "risk".into(),
+            OrderSource::Settlement => "settlement".into(),
+            OrderSource::Ios => "ios".into(),
+            OrderSource::Android => "android".into(),
+            OrderSource::Windows => "windows".into(),
+            OrderSource::Mac => "mac".into(),
+            OrderSource::Trigger => "trigger".into(),
+            OrderSource::Tpsl => "tpsl".into(),
+            OrderSource::Unknown(source) => source,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum LiquidationType {
+    NotLiquidated,
+    // long and short positions netted
+    Netting,
+    PartialTakeover,
+    FullTakeover,
+    Unknown(String),
+}
+
+impl LiquidationType {
+    pub fn is_liquidation(&self) -> bool {
+        !matches!(self, Self::NotLiquidated)
+    }
+}
+
+impl From<String> for LiquidationType {
+    fn from(code: String) -> Self {
+        match code.as_str() {
+            "0" => Self::NotLiquidated,
+            "1" => Self::Netting,
+            "2" => Self::PartialTakeover,
+            "3" => Self::FullTakeover,
+            _ => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<LiquidationType> for String {
+    fn from(liquidation_type: LiquidationType) -> Self {
+        match liquidation_type {
+            LiquidationType::NotLiquidated => "0".into(),
+            LiquidationType::Netting => "1".into(),
+            LiquidationType::PartialTakeover => "2".into(),
+            LiquidationType::FullTakeover => "3".into(),
+            LiquidationType::Unknown(code) => code,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TriggerOpenOrder {
     symbol: String,
-    contract_code: String,
+    contract_code: ContractCode,
     contract_type: String,
     trigger_type: String,
     volume: f64,
@@ -177,7 +475,8 @@ pub struct TriggerOpenOrder {
     order_source: String,
     trigger_price: f64,
     order_price: f64,
-    created_at: u64,
+    #[serde(with = "ts_milliseconds")]
+    created_at: DateTime<Utc>,
     order_price_type: String,
     status: u32,
 }
@@ -186,10 +485,10 @@ pub struct TriggerOpenOrder {
 pub struct TriggerHisOrder {
     symbol: String,
     contract_type: String,
-    contract_code: String,
+    contract_code: ContractCode,
     trigger_type: String,
     volume: f64,
-    order_type: u32,
+    pub order_type: OrderType,
     direction: String,
     offset: String,
     lever_rate: u32,
@@ -197,32 +496,93 @@ pub struct TriggerHisOrder {
     order_id_str: String,
     relation_order_id: String,
     order_price_type: String,
-    status: u32,
-    order_source: String,
+    pub status: TriggerOrderStatus,
+    pub order_source: OrderSource,
     trigger_price: f64,
     triggered_price: Option<f64>,
     order_price: f64,
-    created_at: u64,
-    triggered_at: Option<u64>,
-    order_insert_at: u64,
-    canceled_at: u64,
+    #[serde(with = "ts_milliseconds")]
+    created_at: DateTime<Utc>,
+    #[serde(default, with = "opt_ts_milliseconds")]
+    triggered_at: Option<DateTime<Utc>>,
+    #[serde(with = "opt_ts_milliseconds")]
+    order_insert_at: Option<DateTime<Utc>>,
+    #[serde(with = "opt_ts_milliseconds")]
+    canceled_at: Option<DateTime<Utc>>,
     fail_code: Option<u32>,
     fail_reason: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrackOpenOrder {
+    pub symbol: String,
+    pub contract_code: ContractCode,
+    pub contract_type: String,
+    pub volume: f64,
+    pub order_type: u32,
+    pub direction: String,
+    pub offset: String,
+    pub lever_rate: u32,
+    pub order_id: u64,
+    pub order_id_str: String,
+    pub order_source: OrderSource,
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+    pub order_price_type: String,
+    pub status: u32,
+    pub callback_rate: f64,
+    pub active_price: f64,
+    pub is_active: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrackHisOrder {
+    pub symbol: String,
+    pub contract_code: ContractCode,
+    pub contract_type: String,
+    pub volume: f64,
+    pub order_type: u32,
+    pub direction: String,
+    pub offset: String,
+    pub lever_rate: u32,
+    pub order_id: u64,
+    pub order_id_str: String,
+    pub order_source: OrderSource,
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+    pub order_price_type: String,
+    pub status: u32,
+    pub callback_rate: f64,
+    pub active_price: f64,
+    pub is_active: u32,
+    #[serde(default, with = "opt_ts_milliseconds")]
+    pub canceled_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "opt_ts_milliseconds")]
+    pub update_time: Option<DateTime<Utc>>,
+    pub fail_code: Option<u32>,
+    pub fail_reason: Option<String>,
+    pub triggered_price: Option<f64>,
+    pub formula_price: Option<f64>,
+    pub real_volume: Option<f64>,
+    pub relation_order_id: Option<String>,
+    pub market_limit_price: Option<f64>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct OrderDetail {
     pub symbol: String,
     pub contract_type: String,
-    pub contract_code: String,
+    pub contract_code: ContractCode,
     pub lever_rate: u32,
     pub direction: String,
     pub offset: String,
     pub volume: f64,
     pub price: f64,
-    pub created_at: u64,
-    pub canceled_at: u64,
-    pub order_source: String,
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "opt_ts_milliseconds")]
+    pub canceled_at: Option<DateTime<Utc>>,
+    pub order_source: OrderSource,
     pub order_price_type: String,
     pub margin_frozen: f64,
     pub profit: f64,
@@ -234,7 +594,7 @@ pub struct OrderDetail {
     pub adjust_value: f64,
     pub fee: f64,
     pub fee_asset: String,
-    pub liquidation_type: String,
+    pub liquidation_type: LiquidationType,
     pub trades: Vec<TradeItem>,
 }
 
@@ -247,25 +607,27 @@ pub struct TradeItem {
     pub trade_fee: f64,
     pub fee_asset: String,
     pub role: String,
-    pub created_at: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct OpenOrder {
     pub symbol: String,
-    pub contract_code: String,
+    pub contract_code: ContractCode,
     pub contract_type: String,
     pub volume: f64,
     pub price: f64,
     pub order_price_type: String,
-    pub order_type: u32,
+    pub order_type: OrderType,
     pub direction: String,
     pub offset: String,
     pub lever_rate: u32,
     pub order_id: u64,
     pub order_id_str: String,
-    pub client_order_id: Option<u64>,
-    pub created_at: u64,
+    pub client_order_id: Option<ClientOrderId>,
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
     pub trade_volume: f64,
     pub trade_turnover: f64,
     pub fee: f64,
@@ -273,15 +635,15 @@ pub struct OpenOrder {
     pub trade_avg_price: Option<f64>,
     pub margin_frozen: f64,
     pub profit: f64,
-    pub status: u32,
-    pub order_source: String,
+    pub status: OrderStatus,
+    pub order_source: OrderSource,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct OrderId {
     pub order_id: u64,
     pub order_id_str: String,
-    pub client_order_id: Option<u64>,
+    pub client_order_id: Option<ClientOrderId>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -302,7 +664,44 @@ pub struct BatchOrderSuccess {
     pub index: u32,
     pub order_id: u64,
     pub order_id_str: String,
-    pub client_order_id: Option<u64>,
+    pub client_order_id: Option<ClientOrderId>,
+}
+
+// Outcome of one order in a chunked batch submission
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum BatchOrderResult {
+    Placed(OrderId),
+    Rejected(BatchOrderErrors),
+    // the request carrying this order failed as a whole
+    Failed(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BatchOrderOutcome {
+    pub request: OrderRequest,
+    pub result: BatchOrderResult,
+}
+
+// Result of rolling a position to the next expiry
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RollOutcome {
+    pub from: ContractCode,
+    pub to: ContractCode,
+    pub close: OrderId,
+    // the close leg stands even when the open leg fails
+    pub open: Result<OrderId, String>,
+}
+
+// Result of a cancel-replace
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReplaceOutcome {
+    // final state of the replaced order
+    pub old_order: Order,
+    // contracts filled between the start of the swap and the confirmed cancel
+    pub filled_during_swap: u32,
+    // `None` when the old order filled completely and nothing was placed
+    pub new_order: Option<OrderId>,
+    pub new_client_order_id: Option<ClientOrderId>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -318,19 +717,35 @@ pub struct CancelError {
     pub err_msg: String,
 }
 
+// Outcome of one id in a chunked cancel
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum CancelResult {
+    Cancelled,
+    Rejected(CancelError),
+    // the request carrying this id failed as a whole
+    Failed(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CancelOutcome {
+    pub id: String,
+    pub result: CancelResult,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct HisOrder {
     pub order_id: u64,
     pub order_id_str: String,
     pub symbol: String,
-    pub contract_code: String,
+    pub contract_code: ContractCode,
     pub lever_rate: u32,
     pub direction: String,
     pub offset: String,
     pub volume: u32,
     pub price: f64,
-    pub create_date: u64,
-    pub order_source: String,
+    #[serde(with = "ts_milliseconds")]
+    pub create_date: DateTime<Utc>,
+    pub order_source: OrderSource,
     pub order_price_type: u32,
     pub margin_frozen: f64,
     pub profit: f64,
@@ -339,9 +754,9 @@ pub struct HisOrder {
     pub fee: f64,
     pub fee_asset: String,
     pub trade_avg_price: Option<f64>,
-    pub status: u32,
-    pub order_type: u32,
-    pub liquidation_type: String
+    pub status: OrderStatus,
+    pub order_type: OrderType,
+    pub liquidation_type: LiquidationType
 }
 
 
@@ -361,14 +776,15 @@ pub struct MatchTradeItem {
     pub order_id: u64,
     pub order_id_str: String,
     pub symbol: String,
-    pub order_source: String,
-    pub contract_code: String,
+    pub order_source: OrderSource,
+    pub contract_code: ContractCode,
     pub direction: String,
     pub offset: String,
     pub trade_volume: u32,
     pub trade_price: f64,
     pub trade_turnover: u32,
-    pub create_date: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub create_date: DateTime<Utc>,
     pub offset_profitloss: f64,
     pub trade_fee: f64,
     pub fee_asset: String,
@@ -388,6 +804,83 @@ pub struct TransferLimit {
     pub net_transfer_out_max_daily: f64,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CancelAfter {
+    #[serde(with = "ts_milliseconds")]
+    pub current_time: DateTime<Utc>,
+    #[serde(with = "ts_milliseconds")]
+    pub trigger_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LeverRate {
+    pub symbol: String,
+    pub lever_rate: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AvailableLevelRate {
+    pub symbol: String,
+    pub available_level_rate: String,
+}
+
+impl AvailableLevelRate {
+    pub fn levels(&self) -> Vec<u32> {
+        self.available_level_rate
+            .split(',')
+            .filter_map(|l| l.trim().parse().ok())
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OrderLimit {
+    pub order_price_type: String,
+    pub list: Vec<OrderLimitItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OrderLimitItem {
+    pub symbol: String,
+    pub types: Vec<OrderLimitType>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OrderLimitType {
+    pub contract_type: String,
+    pub open_limit: f64,
+    pub close_limit: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContractFee {
+    pub symbol: String,
+    #[serde(deserialize_with = "from_str")]
+    pub open_maker_fee: f64,
+    #[serde(deserialize_with = "from_str")]
+    pub open_taker_fee: f64,
+    #[serde(deserialize_with = "from_str")]
+    pub close_maker_fee: f64,
+    #[serde(deserialize_with = "from_str")]
+    pub close_taker_fee: f64,
+    #[serde(deserialize_with = "from_str")]
+    pub delivery_fee: f64,
+    pub fee_asset: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PositionLimit {
+    pub symbol: String,
+    pub list: Vec<PositionLimitItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PositionLimitItem {
+    pub contract_type: String,
+    pub buy_limit: f64,
+    pub sell_limit: f64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TransferResponse {
     pub status: String,
@@ -404,7 +897,8 @@ pub struct OrderBook {
     pub asks: Vec<(f64, f64)>,
     pub mrid: u64,
     pub id: u64,
-    pub ts: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
     pub version: u64,
     pub ch: String,
 }
@@ -415,7 +909,8 @@ pub struct IncrementalOrderBook {
     pub asks: Vec<(f64, f64)>,
     pub mrid: u64,
     pub id: u64,
-    pub ts: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
     pub version: u64,
     pub ch: String,
     pub event: String,
@@ -450,19 +945,141 @@ pub struct Merged {
     pub bid: (f64, f64),
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BatchMerged {
+    pub id: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
+    pub symbol: String,
+    pub vol: String,
+    pub count: f64,
+    pub open: String,
+    pub close: String,
+    pub low: String,
+    pub high: String,
+    pub amount: String,
+    pub ask: Option<(f64, f64)>,
+    pub bid: Option<(f64, f64)>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OpenInterest {
+    pub symbol: String,
+    pub contract_type: String,
+    pub contract_code: ContractCode,
+    pub volume: f64,
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HisOpenInterest {
+    pub symbol: String,
+    pub contract_type: String,
+    pub tick: Vec<HisOpenInterestItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HisOpenInterestItem {
+    pub volume: f64,
+    pub amount_type: u32,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EliteRatio {
+    pub symbol: String,
+    pub list: Vec<EliteRatioItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EliteRatioItem {
+    pub buy_ratio: f64,
+    pub sell_ratio: f64,
+    pub locked_ratio: Option<f64>,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InsuranceFund {
+    pub symbol: String,
+    pub tick: Vec<InsuranceFundItem>,
+    pub total_page: Option<u32>,
+    pub current_page: Option<u32>,
+    pub total_size: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InsuranceFundItem {
+    pub insurance_fund: f64,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdjustFactor {
+    pub symbol: String,
+    pub list: Vec<AdjustFactorItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdjustFactorItem {
+    pub lever_rate: u32,
+    pub ladders: Vec<AdjustFactorLadder>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdjustFactorLadder {
+    pub min_size: Option<f64>,
+    pub max_size: Option<f64>,
+    pub ladder: u32,
+    pub adjust_factor: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RiskInfo {
+    pub symbol: String,
+    pub insurance_fund: f64,
+    pub estimated_clawback: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IndexPrice {
+    pub symbol: String,
+    pub index_price: f64,
+    #[serde(with = "ts_milliseconds")]
+    pub index_ts: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeliveryPrice {
+    pub delivery_price: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EstimatedSettlementPrice {
+    pub symbol: String,
+    pub contract_type: String,
+    pub contract_code: ContractCode,
+    pub estimated_settlement_price: Option<f64>,
+    pub settlement_type: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PriceLimit {
     pub symbol: String,
     pub high_limit: f64,
     pub low_limit: f64,
-    pub contract_code: String,
+    pub contract_code: ContractCode,
     pub contract_type: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Trade {
     pub ch: String,
-    pub ts: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
     pub tick: TradeDetail,
     pub status: Option<String>,
 }
@@ -470,15 +1087,19 @@ pub struct Trade {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TradeDetail {
     pub id: u64,
-    pub ts: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
     pub data: Vec<TradeDetailItem>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TradeDetailItem {
-    pub amount: u32,
-    pub ts: u64,
+    #[serde(deserialize_with = "string_as_f64")]
+    pub amount: f64,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
     pub id: u64,
+    #[serde(deserialize_with = "string_as_f64")]
     pub price: f64,
     pub direction: String,
 }
@@ -508,6 +1129,24 @@ impl<'de> Visitor<'de> for F64Visitor {
             })
         }
     }
+    fn visit_f64<E>(self, value: f64) -> Result<f64, E>
+    where
+        E: de::Error,
+    {
+        Ok(value)
+    }
+    fn visit_u64<E>(self, value: u64) -> Result<f64, E>
+    where
+        E: de::Error,
+    {
+        Ok(value as f64)
+    }
+    fn visit_i64<E>(self, value: i64) -> Result<f64, E>
+    where
+        E: de::Error,
+    {
+        Ok(value as f64)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -517,16 +1156,155 @@ pub struct BatchOrderRequest {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct OrderRequest {
-   pub contract_code: Option<String>,
+   pub contract_code: Option<ContractCode>,
    pub symbol: Option<String>,
    pub contract_type: Option<String>,
-   pub client_order_id: Option<u64>,
+   pub client_order_id: Option<ClientOrderId>,
    pub price: Option<f64>,
    pub volume: u32, 
    pub direction: String,
    pub offset: String,
    pub lever_rate: u32,
    pub order_price_type: String,
+   #[serde(flatten)]
+   pub tpsl: Option<OrderTpsl>,
+}
+
+// Take-profit / stop-loss attached to a new order
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct OrderTpsl {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tp_trigger_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tp_order_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tp_order_price_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sl_trigger_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sl_order_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sl_order_price_type: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TpslOrder {
+    pub tp_order: Option<TpslOrderId>,
+    pub sl_order: Option<TpslOrderId>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TpslOrderId {
+    pub order_id: u64,
+    pub order_id_str: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TpslOpenOrder {
+    pub symbol: String,
+    pub contract_code: ContractCode,
+    pub contract_type: String,
+    pub volume: f64,
+    pub order_type: u32,
+    pub tpsl_order_type: String,
+    pub direction: String,
+    pub order_id: u64,
+    pub order_id_str: String,
+    pub order_source: OrderSource,
+    pub trigger_type: String,
+    pub trigger_price: f64,
+    pub order_price: f64,
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+    pub order_price_type: String,
+    pub status: u32,
+    pub source_order_id: Option<String>,
+    pub relation_tpsl_order_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TpslHisOrder {
+    pub symbol: String,
+    pub contract_code: ContractCode,
+    pub contract_type: String,
+    pub volume: f64,
+    pub order_type: u32,
+    pub tpsl_order_type: String,
+    pub direction: String,
+    pub order_id: u64,
+    pub order_id_str: String,
+    pub order_source: OrderSource,
+    pub trigger_type: String,
+    pub trigger_price: f64,
+    pub order_price: f64,
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+    pub order_price_type: String,
+    pub status: u32,
+    pub source_order_id: Option<String>,
+    pub relation_tpsl_order_id: Option<String>,
+    #[serde(default, with = "opt_ts_milliseconds")]
+    pub canceled_at: Option<DateTime<Utc>>,
+    pub fail_code: Option<u32>,
+    pub fail_reason: Option<String>,
+    pub triggered_price: Option<f64>,
+    pub relation_order_id: Option<String>,
+    #[serde(default, with = "opt_ts_milliseconds")]
+    pub update_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelationTpslOrder {
+    pub symbol: String,
+    pub contract_code: ContractCode,
+    pub contract_type: String,
+    pub volume: f64,
+    pub price: f64,
+    pub order_price_type: String,
+    pub direction: String,
+    pub offset: String,
+    pub lever_rate: u32,
+    pub order_id: u64,
+    pub order_id_str: String,
+    pub client_order_id: Option<ClientOrderId>,
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+    pub trade_volume: f64,
+    pub trade_turnover: f64,
+    pub fee: f64,
+    pub trade_avg_price: Option<f64>,
+    pub margin_frozen: f64,
+    pub profit: f64,
+    pub status: u32,
+    pub order_type: u32,
+    pub order_source: OrderSource,
+    pub fee_asset: String,
+    #[serde(default, with = "opt_ts_milliseconds")]
+    pub canceled_at: Option<DateTime<Utc>>,
+    pub tpsl_order_info: Vec<TpslOrderInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TpslOrderInfo {
+    pub volume: f64,
+    pub tpsl_order_type: String,
+    pub direction: String,
+    pub order_id: u64,
+    pub order_id_str: String,
+    pub trigger_type: String,
+    pub trigger_price: f64,
+    pub order_price: f64,
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+    pub order_price_type: String,
+    pub relation_tpsl_order_id: Option<String>,
+    pub status: u32,
+    #[serde(default, with = "opt_ts_milliseconds")]
+    pub canceled_at: Option<DateTime<Utc>>,
+    pub fail_code: Option<u32>,
+    pub fail_reason: Option<String>,
+    pub triggered_price: Option<f64>,
+    pub relation_order_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -534,30 +1312,32 @@ pub struct OrderWSResponse {
     pub op: String,
     pub topic: String,
     pub uid: String,
-    pub ts: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
     pub symbol: String,
-    pub contract_code: String,
+    pub contract_code: ContractCode,
     pub contract_type: String,
     pub volume: u32,
     pub price: f64,
     pub order_price_type: String,
     pub direction: String,
     pub offset: String,
-    pub status: u32,
+    pub status: OrderStatus,
     pub lever_rate: u32,
     pub order_id: u64,
     pub order_id_str: String,
-    pub client_order_id: Option<u64>,
-    pub order_source: String,
-    pub order_type: u32,
-    pub created_at: u64,
+    pub client_order_id: Option<ClientOrderId>,
+    pub order_source: OrderSource,
+    pub order_type: OrderType,
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
     pub trade_volume: u32,
     pub trade_turnover: f64,
     pub fee: f64,
     pub trade_avg_price: f64,
     pub margin_frozen: f64,
     pub profit: f64,
-    pub liquidation_type: String,
+    pub liquidation_type: LiquidationType,
     pub trade: Vec<TradeSubItem>,
 } 
 
@@ -566,15 +1346,16 @@ pub struct MatchOrderWSResponse {
     pub op: String,
     pub topic: String,
     pub uid: String,
-    pub ts: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
     pub symbol: String,
-    pub contract_code: String,
+    pub contract_code: ContractCode,
     pub contract_type: String,
-    pub status: u32,
+    pub status: OrderStatus,
     pub order_id: u64,
     pub order_id_str: String,
-    pub client_order_id: Option<u64>,
-    pub order_type: u32,
+    pub client_order_id: Option<ClientOrderId>,
+    pub order_type: OrderType,
     pub volume: u32,
     pub trade_volume: u32,
     pub trade: Vec<TradeSubItem>,
@@ -589,7 +1370,8 @@ pub struct TradeSubItem {
     pub trade_fee: Option<f64>,
     pub fee_asset: Option<String>,
     pub trade_turnover: f64,
-    pub created_at: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
     pub role: String,
 }
 
@@ -598,7 +1380,8 @@ pub struct PositionSubs
 {
     pub op: String,
     pub topic: String,
-    pub ts: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
     pub event: String,
     pub data: Vec<Position>,
 }
@@ -607,7 +1390,8 @@ pub struct PositionSubs
 pub struct HistoryTrade {
     pub ch: String,
     pub status: String,
-    pub ts: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
     pub data: Vec<HistoryTradeItem>,
 }
 
@@ -615,7 +1399,8 @@ pub struct HistoryTrade {
 pub struct HistoryTradeItem {
     pub data: Vec<TradeDetailItem>,
     pub id: u64,
-    pub ts: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
 }
 
 
@@ -624,6 +1409,7 @@ pub enum Subscription {
     Market,            // market
     Account,      // private account
     Index,        // index
+    CenterNotification, // system status
 }
 
 
@@ -650,11 +1436,15 @@ pub enum WebsocketEvent {
     Liquidation(WSAccountResponse<Vec<Liquidation>>),
     ContractInfo(WSAccountResponse<Vec<ContractInfo>>),
     TriggerOrder(WSAccountResponse<Vec<TriggerHisOrder>>),
+    TrackOrder(WSAccountResponse<Vec<TrackHisOrder>>),
 
     //Index
     Basis(WSMarketResponse<Basis>),
     Index(WSMarketResponse<Index>),
 
+    //System status
+    ServiceHeartbeat(WSAccountResponse<ServiceHeartbeat>),
+
     OpStatus(OpStatus),
 
     //Other
@@ -662,13 +1452,15 @@ pub enum WebsocketEvent {
     Pong,
     Binary(Vec<u8>), // Unexpected, unparsed
     Text(String),
+    Raw(serde_json::Value), // Valid JSON of an unknown shape
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SubStatus {
     pub id: String,
     pub subbed: Option<String>,
-    pub ts: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
     pub status: String,
     #[serde(rename = "err-code")]
     pub err_code: Option<String>,
@@ -727,35 +1519,102 @@ pub struct Index {
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Liquidation {
     pub symbol: String,
-    pub contract_code: String,
+    pub contract_code: ContractCode,
     pub direction: String,
     pub offset: String,
     pub volume: f64,
     pub price: f64,
-    pub created_at: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ContractInfo {
     pub symbol: String,
-    pub contract_code: String,
+    pub contract_code: ContractCode,
     pub contract_type: String,
     pub contract_size: f64,
     pub price_tick: f64,
-    pub delivery_date: String,
-    pub create_date: String,
+    #[serde(with = "yyyymmdd")]
+    pub delivery_date: NaiveDate,
+    #[serde(with = "yyyymmdd")]
+    pub create_date: NaiveDate,
     pub contract_status: u32,
 }
 
 
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceHeartbeat {
+    pub heartbeat: u32,
+    pub estimated_recovery_time: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Heartbeat {
+    pub heartbeat: u32,
+    pub estimated_recovery_time: Option<u64>,
+    pub swap_heartbeat: Option<u32>,
+    pub swap_estimated_recovery_time: Option<u64>,
+    pub linear_swap_heartbeat: Option<u32>,
+    pub linear_swap_estimated_recovery_time: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiState {
+    pub symbol: String,
+    pub open: u32,
+    pub close: u32,
+    pub cancel: u32,
+    pub transfer_in: u32,
+    pub transfer_out: u32,
+    pub master_transfer_sub: Option<u32>,
+    pub sub_transfer_master: Option<u32>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BBO {
     pub bid: (f64, f64),
     pub ask: (f64, f64),
     pub id: u64,
-    pub ts: u64,
+    #[serde(with = "ts_milliseconds")]
+    pub ts: DateTime<Utc>,
     pub version: u64,
     pub ch: String,
     pub mrid: u64,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_codes_round_trip() {
+        for code in 0..=12 {
+            assert_eq!(u32::from(OrderStatus::from(code)), code);
+            assert_eq!(u32::from(TriggerOrderStatus::from(code)), code);
+            assert_eq!(u32::from(OrderType::from(code)), code);
+        }
+        assert_eq!(OrderStatus::from(2), OrderStatus::Submitting);
+        assert_ne!(OrderStatus::from(1), OrderStatus::from(2));
+        assert_eq!(serde_json::to_string(&serde_json::from_str::<OrderStatus>("2").unwrap()).unwrap(), "2");
+    }
+
+    #[derive(Deserialize)]
+    struct Cancelled {
+        #[serde(with = "opt_ts_milliseconds")]
+        canceled_at: Option<DateTime<Utc>>,
+    }
+
+    #[test]
+    fn optional_timestamps() {
+        let parse = |v: serde_json::Value| serde_json::from_value::<Cancelled>(v).map(|c| c.canceled_at);
+        assert_eq!(parse(serde_json::json!({ "canceled_at": 0 })).unwrap(), None);
+        assert_eq!(parse(serde_json::json!({ "canceled_at": null })).unwrap(), None);
+        assert_eq!(
+            parse(serde_json::json!({ "canceled_at": 1_600_000_000_000i64 })).unwrap().unwrap().timestamp(),
+            1_600_000_000
+        );
+        // out of chrono's range: an error, not a panic
+        assert!(parse(serde_json::json!({ "canceled_at": i64::MAX })).is_err());
+    }
+}
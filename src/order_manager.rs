@@ -0,0 +1,473 @@
+use crate::{client::HuobiFuture, models::*};
+use chrono::{DateTime, Utc};
+use failure::Fallible;
+use std::collections::{HashMap, HashSet};
+use tracing::*;
+
+// Max ids per contract_order_info request.
+const ORDER_INFO_BATCH: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderState {
+    Submitted,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+}
+
+impl OrderState {
+    // `None` for statuses that do not move the lifecycle, e.g. cancelling.
+    pub fn from_status(status: OrderStatus) -> Option<Self> {
+        match status {
+            OrderStatus::Ready | OrderStatus::Submitting | OrderStatus::Submitted => Some(Self::Submitted),
+            OrderStatus::PartiallyFilled => Some(Self::PartiallyFilled),
+            OrderStatus::PartiallyCancelled | OrderStatus::Cancelled => Some(Self::Cancelled),
+            OrderStatus::Filled => Some(Self::Filled),
+            OrderStatus::Cancelling | OrderStatus::Unknown(_) => None,
+        }
+    }
+
+    pub fn is_terminal(self) -> bool {
+        matches!(self, Self::Filled | Self::Cancelled)
+    }
+
+    fn rank(self) -> u8 {
+        match self {
+            Self::Submitted => 0,
+            Self::PartiallyFilled => 1,
+            Self::Filled | Self::Cancelled => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub trade_id: u64,
+    pub volume: f64,
+    pub price: f64,
+    pub fee: Option<f64>,
+    pub role: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackedOrder {
+    pub order_id: Option<u64>,
+    pub client_order_id: Option<ClientOrderId>,
+    pub symbol: Option<String>,
+    pub contract_code: Option<ContractCode>,
+    pub direction: String,
+    pub offset: String,
+    pub volume: f64,
+    pub price: Option<f64>,
+    pub filled_volume: f64,
+    pub trade_avg_price: Option<f64>,
+    pub fee: f64,
+    pub state: OrderState,
+    pub fills: Vec<Fill>,
+    pub updated_at: Option<DateTime<Utc>>,
+    // kept per order: Huobi trade ids are only unique within an order
+    trade_ids: HashSet<u64>,
+}
+
+impl TrackedOrder {
+    pub fn remaining_volume(&self) -> f64 {
+        (self.volume - self.filled_volume).max(0.0)
+    }
+
+    fn advance(&mut self, state: OrderState) {
+        if !self.state.is_terminal() && state.rank() >= self.state.rank() {
+            self.state = state;
+        }
+    }
+
+    fn add_fill(&mut self, fill: Fill) -> bool {
+        if !self.trade_ids.insert(fill.trade_id) {
+            return false;
+        }
+        self.fills.push(fill);
+
+        let filled: f64 = self.fills.iter().map(|f| f.volume).sum();
+        self.set_filled_volume(filled);
+        true
+    }
+
+    // Fill totals only grow; out-of-order updates never shrink them.
+    fn set_filled_volume(&mut self, filled: f64) {
+        if filled > self.filled_volume {
+            self.filled_volume = filled;
+        }
+        if self.volume > 0.0 && self.filled_volume >= self.volume {
+            self.advance(OrderState::Filled);
+        } else if self.filled_volume > 0.0 {
+            self.advance(OrderState::PartiallyFilled);
+        }
+    }
+
+    fn touch(&mut self, ts: DateTime<Utc>) {
+        if self.updated_at.map_or(true, |t| ts > t) {
+            self.updated_at = Some(ts);
+        }
+    }
+}
+
+// Tracks orders from submission to a terminal state, combining REST acknowledgements,
+// `orders.*` / `matchOrders.*` notifications and `contract_order_info` polls.
+#[derive(Debug, Default)]
+pub struct OrderManager {
+    orders: Vec<TrackedOrder>,
+    by_order_id: HashMap<u64, usize>,
+    by_client_id: HashMap<ClientOrderId, usize>,
+}
+
+impl OrderManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, order_id: u64) -> Option<&TrackedOrder> {
+        self.by_order_id.get(&order_id).map(|&i| &self.orders[i])
+    }
+
+    pub fn get_by_client_id(&self, client_order_id: ClientOrderId) -> Option<&TrackedOrder> {
+        self.by_client_id.get(&client_order_id).map(|&i| &self.orders[i])
+    }
+
+    pub fn open_orders(&self) -> impl Iterator<Item = &TrackedOrder> {
+        self.orders.iter().filter(|o| !o.state.is_terminal())
+    }
+
+    // Forget orders that reached a terminal state.
+    pub fn prune(&mut self) {
+        self.orders.retain(|o| !o.state.is_terminal());
+        self.reindex();
+    }
+
+    // Register an order before it is sent, so notifications arriving ahead of the REST
+    // response can be matched by client order id.
+    pub fn submit(&mut self, request: &OrderRequest) -> &TrackedOrder {
+        let idx = self.slot(None, request.client_order_id);
+        let order = &mut self.orders[idx];
+
+        order.symbol = request.symbol.clone().or_else(|| order.symbol.take());
+        order.contract_code = request.contract_code.clone().or_else(|| order.contract_code.take());
+        order.direction = request.direction.clone();
+        order.offset = request.offset.clone();
+        order.volume = f64::from(request.volume);
+        order.price = request.price;
+
+        &self.orders[idx]
+    }
+
+    pub fn on_order_id(&mut self, ack: &OrderId) -> &TrackedOrder {
+        let idx = self.slot(Some(ack.order_id), ack.client_order_id);
+        &self.orders[idx]
+    }
+
+    pub fn on_batch_order(&mut self, batch: &BatchOrder) {
+        for s in &batch.success {
+            self.slot(Some(s.order_id), s.client_order_id);
+        }
+    }
+
+    pub fn on_order_event(&mut self, msg: &OrderWSResponse) -> &TrackedOrder {
+        let idx = self.slot(Some(msg.order_id), msg.client_order_id);
+        let order = &mut self.orders[idx];
+
+        order.symbol = Some(msg.symbol.clone());
+        order.contract_code = Some(msg.contract_code.clone());
+        order.direction = msg.direction.clone();
+        order.offset = msg.offset.clone();
+        order.volume = f64::from(msg.volume);
+        order.price = Some(msg.price);
+        order.fee = msg.fee;
+        if msg.trade_avg_price > 0.0 {
+            order.trade_avg_price = Some(msg.trade_avg_price);
+        }
+
+        for t in &msg.trade {
+            order.add_fill(fill_from(t));
+        }
+        order.set_filled_volume(f64::from(msg.trade_volume));
+        if let Some(state) = OrderState::from_status(msg.status) {
+            order.advance(state);
+        }
+        order.touch(msg.ts);
+
+        &self.orders[idx]
+    }
+
+    pub fn on_match_event(&mut self, msg: &MatchOrderWSResponse) -> &TrackedOrder {
+        let idx = self.slot(Some(msg.order_id), msg.client_order_id);
+        let order = &mut self.orders[idx];
+
+        order.symbol = Some(msg.symbol.clone());
+        order.contract_code = Some(msg.contract_code.clone());
+        order.volume = f64::from(msg.volume);
+
+        for t in &msg.trade {
+            order.add_fill(fill_from(t));
+        }
+        order.set_filled_volume(f64::from(msg.trade_volume));
+        if let Some(state) = OrderState::from_status(msg.status) {
+            order.advance(state);
+        }
+        order.touch(msg.ts);
+
+        &self.orders[idx]
+    }
+
+    pub fn on_order_info(&mut self, info: &Order) -> &TrackedOrder {
+        let idx = self.slot(Some(info.order_id), info.client_order_id);
+        let order = &mut self.orders[idx];
+
+        order.symbol = Some(info.symbol.clone());
+        order.contract_code = Some(info.contract_code.clone());
+        order.direction = info.direction.clone();
+        order.offset = info.offset.clone();
+        order.volume = info.volume;
+        order.price = Some(info.price);
+        order.fee = info.fee;
+        if info.trade_avg_price.is_some() {
+            order.trade_avg_price = info.trade_avg_price;
+        }
+
+        order.set_filled_volume(f64::from(info.trade_volume));
+        if let Some(state) = OrderState::from_status(info.status) {
+            order.advance(state);
+        }
+
+        &self.orders[idx]
+    }
+
+    // Feed websocket events from the handler; unrelated events are ignored.
+    pub fn handle_event(&mut self, event: &WebsocketEvent) {
+        match event {
+            WebsocketEvent::Order(msg) => {
+                self.on_order_event(msg);
+            }
+            WebsocketEvent::MatchOrder(msg) => {
+                self.on_match_event(msg);
+            }
+            _ => (),
+        }
+    }
+
+    // Poll every open order through REST, e.g. after a reconnect or a missed update.
+    pub async fn reconcile(&mut self, client: &HuobiFuture) -> Fallible<()> {
+        let mut by_symbol: HashMap<String, (Vec<String>, Vec<ClientOrderId>)> = HashMap::new();
+
+        for order in self.open_orders() {
+            let symbol = match &order.symbol {
+                Some(symbol) => symbol.clone(),
+                None => {
+                    warn!("[OrderManager] cannot reconcile order without symbol: {:?}", order.client_order_id);
+                    continue;
+                }
+            };
+            let ids = by_symbol.entry(symbol).or_default();
+            match (order.order_id, order.client_order_id) {
+                (Some(oid), _) => ids.0.push(oid.to_string()),
+                (None, Some(cid)) => ids.1.push(cid),
+                (None, None) => (),
+            }
+        }
+
+        for (symbol, (order_ids, client_ids)) in by_symbol {
+            for chunk in order_ids.chunks(ORDER_INFO_BATCH) {
+                let resp = client.get_order_info(symbol.clone(), chunk.join(","), &[])?.await?;
+                for info in resp.data.unwrap_or_default() {
+                    self.on_order_info(&info);
+                }
+            }
+            for chunk in client_ids.chunks(ORDER_INFO_BATCH) {
+                let resp = client.get_order_info(symbol.clone(), None, chunk)?.await?;
+                for info in resp.data.unwrap_or_default() {
+                    self.on_order_info(&info);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Find or create the entry for an order, linking exchange and client ids.
+    fn slot(&mut self, order_id: Option<u64>, client_order_id: Option<ClientOrderId>) -> usize {
+        let existing = order_id
+            .and_then(|oid| self.by_order_id.get(&oid))
+            .or_else(|| client_order_id.and_then(|cid| self.by_client_id.get(&cid)))
+            .copied();
+
+        let idx = match existing {
+            Some(idx) => idx,
+            None => {
+                self.orders.push(TrackedOrder {
+                    order_id: None,
+                    client_order_id: None,
+                    symbol: None,
+                    contract_code: None,
+                    direction: String::new(),
+                    offset: String::new(),
+                    volume: 0.0,
+                    price: None,
+                    filled_volume: 0.0,
+                    trade_avg_price: None,
+                    fee: 0.0,
+                    state: OrderState::Submitted,
+                    fills: Vec::new(),
+                    updated_at: None,
+                    trade_ids: HashSet::new(),
+                });
+                self.orders.len() - 1
+            }
+        };
+
+        let order = &mut self.orders[idx];
+        if let Some(oid) = order_id {
+            order.order_id = Some(oid);
+            self.by_order_id.insert(oid, idx);
+        }
+        if let Some(cid) = client_order_id {
+            order.client_order_id = Some(cid);
+            self.by_client_id.insert(cid, idx);
+        }
+
+        idx
+    }
+
+    fn reindex(&mut self) {
+        self.by_order_id.clear();
+        self.by_client_id.clear();
+        for (idx, order) in self.orders.iter().enumerate() {
+            if let Some(oid) = order.order_id {
+                self.by_order_id.insert(oid, idx);
+            }
+            if let Some(cid) = order.client_order_id {
+                self.by_client_id.insert(cid, idx);
+            }
+        }
+    }
+}
+
+fn fill_from(t: &TradeSubItem) -> Fill {
+    Fill {
+        trade_id: t.trade_id,
+        volume: f64::from(t.trade_volume),
+        price: t.trade_price,
+        fee: t.trade_fee,
+        role: t.role.clone(),
+        created_at: t.created_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(
+        order_id: u64,
+        client_order_id: u64,
+        status: u32,
+        trade_volume: u32,
+        trades: &[(u64, u32)],
+    ) -> OrderWSResponse {
+        let trade: Vec<_> = trades
+            .iter()
+            .map(|(trade_id, volume)| json!({
+                "trade_id": trade_id, "id": format!("{}-{}", trade_id, order_id), "trade_volume": volume,
+                "trade_price": 8000.0, "trade_fee": -0.0001, "fee_asset": "BTC", "trade_turnover": 100.0,
+                "created_at": 0, "role": "taker"
+            }))
+            .collect();
+        serde_json::from_value(json!({
+            "op": "notify", "topic": "orders.btc", "uid": "1", "ts": 0, "symbol": "BTC",
+            "contract_code": "BTC200925", "contract_type": "quarter", "volume": 5, "price": 8000.0,
+            "order_price_type": "limit", "direction": "buy", "offset": "open", "status": status,
+            "lever_rate": 10, "order_id": order_id, "order_id_str": order_id.to_string(),
+            "client_order_id": client_order_id, "order_source": "api", "order_type": 1, "created_at": 0,
+            "trade_volume": trade_volume, "trade_turnover": 0.0, "fee": 0.0, "trade_avg_price": 8000.0,
+            "margin_frozen": 0.0, "profit": 0.0, "liquidation_type": "0", "trade": trade
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn repeated_trade_ids_count_once() {
+        let mut manager = OrderManager::new();
+        manager.on_order_event(&event(1, 11, 4, 2, &[(100, 2)]));
+        let order = manager.on_order_event(&event(1, 11, 4, 2, &[(100, 2)]));
+        assert_eq!(order.fills.len(), 1);
+        assert_eq!(order.filled_volume, 2.0);
+
+        // the same trade id on another order is a different trade
+        let other = manager.on_order_event(&event(2, 12, 4, 1, &[(100, 1)]));
+        assert_eq!(other.fills.len(), 1);
+    }
+
+    #[test]
+    fn late_updates_do_not_regress_the_state() {
+        let mut manager = OrderManager::new();
+        manager.on_order_event(&event(1, 11, 6, 5, &[(100, 5)]));
+        manager.on_order_event(&event(1, 11, 3, 0, &[]));
+        let order = manager.on_order_event(&event(1, 11, 4, 2, &[]));
+
+        assert_eq!(order.state, OrderState::Filled);
+        assert_eq!(order.filled_volume, 5.0);
+    }
+
+    #[test]
+    fn filled_volume_never_shrinks() {
+        let mut manager = OrderManager::new();
+        manager.on_order_event(&event(1, 11, 4, 3, &[]));
+        let order = manager.on_order_event(&event(1, 11, 4, 1, &[]));
+
+        assert_eq!(order.filled_volume, 3.0);
+        assert_eq!(order.state, OrderState::PartiallyFilled);
+    }
+
+    #[test]
+    fn submit_is_linked_to_the_later_ack() {
+        let mut manager = OrderManager::new();
+        manager.submit(&OrderRequest {
+            contract_code: None,
+            symbol: Some("BTC".into()),
+            contract_type: Some("quarter".into()),
+            client_order_id: Some(ClientOrderId(11)),
+            price: Some(8000.0),
+            volume: 5,
+            direction: "buy".into(),
+            offset: "open".into(),
+            lever_rate: 10,
+            order_price_type: "limit".into(),
+            tpsl: None,
+        });
+        manager.on_order_id(&OrderId { order_id: 1, order_id_str: "1".into(), client_order_id: Some(ClientOrderId(11)) });
+
+        assert_eq!(manager.orders.len(), 1);
+        let order = manager.get(1).unwrap();
+        assert_eq!(order.client_order_id, Some(ClientOrderId(11)));
+        assert_eq!(order.symbol.as_deref(), Some("BTC"));
+        assert_eq!(order.volume, 5.0);
+    }
+
+    #[test]
+    fn lookups_survive_a_prune() {
+        let mut manager = OrderManager::new();
+        manager.on_order_event(&event(1, 11, 6, 5, &[]));
+        manager.on_order_event(&event(2, 12, 3, 0, &[]));
+        manager.on_order_event(&event(3, 13, 7, 0, &[]));
+        manager.on_order_event(&event(4, 14, 4, 2, &[]));
+        manager.prune();
+
+        assert!(manager.get(1).is_none());
+        assert!(manager.get_by_client_id(ClientOrderId(13)).is_none());
+        assert_eq!(manager.get(2).unwrap().client_order_id, Some(ClientOrderId(12)));
+        assert_eq!(manager.get_by_client_id(ClientOrderId(14)).unwrap().order_id, Some(4));
+
+        // updates after the prune land on the right order
+        let order = manager.on_order_event(&event(4, 14, 6, 5, &[]));
+        assert_eq!(order.client_order_id, Some(ClientOrderId(14)));
+        assert_eq!(manager.get(2).unwrap().state, OrderState::Submitted);
+    }
+}
@@ -0,0 +1,346 @@
+use crate::{inverse, models::*};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+// (order id, trade id) pairs remembered for de-duplicating replayed fills. Older
+// replays are still caught by the position push that covers them.
+const SEEN_TRADES: usize = 10_000;
+
+#[derive(Debug, Clone)]
+pub struct BookPosition {
+    pub symbol: String,
+    pub contract_code: ContractCode,
+    pub contract_type: String,
+    pub direction: String,
+    pub volume: f64,
+    pub available: f64,
+    pub frozen: f64,
+    pub cost_open: f64,
+    pub cost_hold: f64,
+    pub lever_rate: u32,
+    pub realized_pnl: f64,
+    pub mark_price: Option<f64>,
+    pub unrealized_pnl: Option<f64>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// Positions keyed by contract code and direction ("buy" / "sell"), fed by the
+// `positions.*` topic, order fills and BBO or mark prices.
+//
+// Position pushes are authoritative. Fills only move a position between pushes: a
+// fill created at or before the last push for its position is already in it.
+#[derive(Debug, Default)]
+pub struct PositionBook {
+    positions: HashMap<(String, String), BookPosition>,
+    contract_sizes: HashMap<String, f64>,
+    marks: HashMap<String, f64>,
+    trade_ids: HashSet<(u64, u64)>,
+    trade_order: VecDeque<(u64, u64)>,
+    synced_at: HashMap<(String, String), DateTime<Utc>>,
+}
+
+impl PositionBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Contract face values in USD, from `get_contract_info`. Unrealized PnL stays `None`
+    // for contracts without a known size.
+    pub fn set_contracts(&mut self, contracts: &[Symbol]) {
+        for c in contracts {
+            self.contract_sizes.insert(c.contract_code.to_string(), c.contract_size);
+        }
+        self.revalue_all();
+    }
+
+    pub fn get(&self, contract_code: &str, direction: &str) -> Option<&BookPosition> {
+        self.positions.get(&(contract_code.to_string(), direction.to_string()))
+    }
+
+    // Copy of every open position, all valued against the same set of prices.
+    pub fn snapshot(&self) -> Vec<BookPosition> {
+        let mut positions: Vec<_> = self.positions.values().cloned().collect();
+        positions.sort_by(|a, b| (&a.contract_code, &a.direction).cmp(&(&b.contract_code, &b.direction)));
+        positions
+    }
+
+    pub fn total_unrealized_pnl(&self, symbol: &str) -> f64 {
+        self.positions
+            .values()
+            .filter(|p| p.symbol == symbol)
+            .filter_map(|p| p.unrealized_pnl)
+            .sum()
+    }
+
+    // Exchange snapshots are authoritative and replace the tracked state.
+    pub fn apply_position(&mut self, position: &Position, ts: DateTime<Utc>) {
+        let key = (position.contract_code.to_string(), position.direction.clone());
+        let synced_at = self.synced_at.entry(key.clone()).or_insert(ts);
+        *synced_at = (*synced_at).max(ts);
+        if position.volume <= 0.0 {
+            self.positions.remove(&key);
+            return;
+        }
+
+        let realized_pnl = self.positions.get(&key).map_or(0.0, |p| p.realized_pnl);
+        let entry = BookPosition {
+            symbol: position.symbol.clone(),
+            contract_code: position.contract_code.clone(),
+            contract_type: position.contract_type.clone(),
+            direction: position.direction.clone(),
+            volume: position.volume,
+            available: position.available,
+            frozen: position.frozen,
+            cost_open: position.cost_open,
+            cost_hold: position.cost_hold,
+            lever_rate: position.lever_rate,
+            realized_pnl,
+            mark_price: None,
+            unrealized_pnl: None,
+            updated_at: Some(ts),
+        };
+        self.positions.insert(key.clone(), entry);
+        self.revalue(&key);
+    }
+
+    pub fn apply_positions(&mut self, positions: &[Position], ts: DateTime<Utc>) {
+        for p in positions {
+            self.apply_position(p, ts);
+        }
+    }
+
+    // Apply a fill ahead of the next position push. Trades already seen, or already
+    // covered by a position push, are ignored.
+    pub fn apply_fill(&mut self, order: &OrderWSResponse, trade: &TradeSubItem) {
+        // trade ids are only unique within an order
+        if !self.remember((order.order_id, trade.trade_id)) {
+            return;
+        }
+
+        let volume = f64::from(trade.trade_volume);
+        let price = trade.trade_price;
+        let opening = order.offset == "open";
+        // closing a long is a sell, closing a short is a buy
+        let direction = match (opening, order.direction.as_str()) {
+            (true, d) => d.to_string(),
+            (false, "buy") => "sell".to_string(),
+            (false, _) => "buy".to_string(),
+        };
+        let key = (order.contract_code.to_string(), direction.clone());
+        if self.synced_at.get(&key).map_or(false, |ts| trade.created_at <= *ts) {
+            return;
+        }
+        let contract_size = self.contract_sizes.get(order.contract_code.as_str()).copied();
+
+        if opening {
+            let entry = self.positions.entry(key.clone()).or_insert_with(|| BookPosition {
+                symbol: order.symbol.clone(),
+                contract_code: order.contract_code.clone(),
+                contract_type: order.contract_type.clone(),
+                direction,
+                volume: 0.0,
+                available: 0.0,
+                frozen: 0.0,
+                cost_open: price,
+                cost_hold: price,
+                lever_rate: order.lever_rate,
+                realized_pnl: 0.0,
+                mark_price: None,
+                unrealized_pnl: None,
+                updated_at: None,
+            });
+            entry.cost_open = inverse::average_entry(entry.volume, entry.cost_open, volume, price);
+            entry.cost_hold = inverse::average_entry(entry.volume, entry.cost_hold, volume, price);
+            entry.volume += volume;
+            entry.available += volume;
+            entry.updated_at = entry.updated_at.max(Some(trade.created_at));
+        } else {
+            let entry = match self.positions.get_mut(&key) {
+                Some(entry) => entry,
+                None => return,
+            };
+            let closed = volume.min(entry.volume);
+            if let Some(size) = contract_size {
+                entry.realized_pnl += inverse::pnl(&entry.direction, closed, size, entry.cost_hold, price);
+            }
+            entry.volume -= closed;
+            entry.available = (entry.available - closed).max(0.0);
+            entry.updated_at = entry.updated_at.max(Some(trade.created_at));
+            if entry.volume <= 0.0 {
+                self.positions.remove(&key);
+                return;
+            }
+        }
+        self.revalue(&key);
+    }
+
+    // Mark by contract code (e.g. "BTC200925") or alias (e.g. "BTC_CQ").
+    pub fn set_mark_price(&mut self, contract: &str, price: f64) {
+        self.marks.insert(contract.to_string(), price);
+        self.revalue_all();
+    }
+
+    pub fn apply_bbo(&mut self, bbo: &BBO) {
+        let contract = match bbo.ch.split('.').nth(1) {
+            Some(contract) => contract,
+            None => return,
+        };
+        let (bid, ask) = (bbo.bid.0, bbo.ask.0);
+        if bid > 0.0 && ask > 0.0 {
+            self.set_mark_price(contract, (bid + ask) / 2.0);
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &WebsocketEvent) {
+        match event {
+            WebsocketEvent::Position(msg) => self.apply_positions(&msg.data, msg.ts),
+            WebsocketEvent::BBO(msg) => self.apply_bbo(&msg.tick),
+            WebsocketEvent::Order(msg) => {
+                for t in &msg.trade {
+                    self.apply_fill(msg, t);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn remember(&mut self, id: (u64, u64)) -> bool {
+        if !self.trade_ids.insert(id) {
+            return false;
+        }
+        self.trade_order.push_back(id);
+        if self.trade_order.len() > SEEN_TRADES {
+            if let Some(old) = self.trade_order.pop_front() {
+                self.trade_ids.remove(&old);
+            }
+        }
+        true
+    }
+
+    fn mark_for(&self, p: &BookPosition) -> Option<f64> {
+        self.marks
+            .get(p.contract_code.as_str())
+            .or_else(|| contract_alias(&p.symbol, &p.contract_type).and_then(|alias| self.marks.get(&alias)))
+            .copied()
+    }
+
+    fn revalue(&mut self, key: &(String, String)) {
+        let mark = match self.positions.get(key) {
+            Some(p) => self.mark_for(p),
+            None => return,
+        };
+        let size = self.contract_sizes.get(&key.0).copied();
+        if let Some(p) = self.positions.get_mut(key) {
+            p.mark_price = mark;
+            p.unrealized_pnl = match (mark, size) {
+                (Some(mark), Some(size)) => Some(inverse::pnl(&p.direction, p.volume, size, p.cost_hold, mark)),
+                _ => None,
+            };
+        }
+    }
+
+    fn revalue_all(&mut self) {
+        let keys: Vec<_> = self.positions.keys().cloned().collect();
+        for key in &keys {
+            self.revalue(key);
+        }
+    }
+}
+
+fn contract_alias(symbol: &str, contract_type: &str) -> Option<String> {
+    let suffix = match contract_type {
+        "this_week" => "CW",
+        "next_week" => "NW",
+        "quarter" => "CQ",
+        "next_quarter" => "NQ",
+        _ => return None,
+    };
+    Some(format!("{}_{}", symbol, suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn fill(order_id: u64, trade_id: u64, volume: u32, created_at: i64) -> OrderWSResponse {
+        serde_json::from_value(json!({
+            "op": "notify", "topic": "orders.btc", "uid": "1", "ts": created_at,
+            "symbol": "BTC", "contract_code": "BTC200925", "contract_type": "quarter",
+            "volume": volume, "price": 8000.0, "order_price_type": "limit", "direction": "buy",
+            "offset": "open", "status": 6, "lever_rate": 10, "order_id": order_id,
+            "order_id_str": order_id.to_string(), "client_order_id": null, "order_source": "api",
+            "order_type": 1, "created_at": created_at, "trade_volume": volume, "trade_turnover": 0.0,
+            "fee": 0.0, "trade_avg_price": 8000.0, "margin_frozen": 0.0, "profit": 0.0,
+            "liquidation_type": "0",
+            "trade": [{
+                "trade_id": trade_id, "id": format!("{}-{}", trade_id, order_id), "trade_volume": volume,
+                "trade_price": 8000.0, "trade_fee": null, "fee_asset": null, "trade_turnover": 0.0,
+                "created_at": created_at, "role": "taker"
+            }]
+        }))
+        .unwrap()
+    }
+
+    fn position(volume: f64) -> Position {
+        serde_json::from_value(json!({
+            "symbol": "BTC", "contract_code": "BTC200925", "contract_type": "quarter", "volume": volume,
+            "available": volume, "frozen": 0.0, "cost_open": 8000.0, "cost_hold": 8000.0,
+            "profit_unreal": 0.0, "profit_rate": 0.0, "profit": 0.0, "position_margin": 0.0,
+            "lever_rate": 10, "direction": "buy", "last_price": 8000.0
+        }))
+        .unwrap()
+    }
+
+    fn apply(book: &mut PositionBook, order: &OrderWSResponse) {
+        for t in &order.trade {
+            book.apply_fill(order, t);
+        }
+    }
+
+    fn volume(book: &PositionBook) -> f64 {
+        book.get("BTC200925", "buy").map_or(0.0, |p| p.volume)
+    }
+
+    #[test]
+    fn trade_ids_are_scoped_to_orders() {
+        let mut book = PositionBook::new();
+        apply(&mut book, &fill(1, 100, 2, 1_000));
+        apply(&mut book, &fill(1, 100, 2, 1_000));
+        apply(&mut book, &fill(2, 100, 3, 1_000));
+        assert_eq!(volume(&book), 5.0);
+    }
+
+    #[test]
+    fn fills_covered_by_a_snapshot_are_skipped() {
+        let mut book = PositionBook::new();
+        apply(&mut book, &fill(1, 100, 2, 1_000));
+        book.apply_position(&position(2.0), Utc.timestamp_millis(2_000));
+
+        // delivered after the snapshot that already counts it
+        apply(&mut book, &fill(2, 200, 3, 1_500));
+        assert_eq!(volume(&book), 2.0);
+
+        apply(&mut book, &fill(3, 300, 1, 2_500));
+        assert_eq!(volume(&book), 3.0);
+
+        book.apply_position(&position(0.0), Utc.timestamp_millis(3_000));
+        apply(&mut book, &fill(4, 400, 1, 2_800));
+        assert_eq!(book.get("BTC200925", "buy").map(|p| p.volume), None);
+    }
+
+    #[test]
+    fn seen_trades_are_bounded() {
+        let mut book = PositionBook::new();
+        for trade_id in 0..SEEN_TRADES as u64 + 10 {
+            apply(&mut book, &fill(1, trade_id, 1, 1_000));
+        }
+        assert_eq!(book.trade_ids.len(), SEEN_TRADES);
+        assert_eq!(book.trade_order.len(), SEEN_TRADES);
+
+        // recent replays are still dropped
+        apply(&mut book, &fill(1, SEEN_TRADES as u64 + 9, 1, 1_000));
+        assert_eq!(volume(&book), (SEEN_TRADES + 10) as f64);
+    }
+}
@@ -0,0 +1,388 @@
+use crate::{client::HuobiFuture, client_order_id::ClientOrderId, error::Error, models::*};
+use failure::Fallible;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickPolicy {
+    // round buys down and sells up to the nearest tick, never crossing further
+    Round,
+    Reject,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ContractLimits {
+    // max contracts held per direction after the order fills
+    pub max_position: Option<f64>,
+    // max USD face value held per direction after the order fills
+    pub max_notional: Option<f64>,
+}
+
+// Opening order accepted by `check` and not yet filled or cancelled.
+#[derive(Debug)]
+struct InFlight {
+    key: (String, String),
+    volume: f64,
+}
+
+#[derive(Debug)]
+struct CheckerState {
+    tick_policy: TickPolicy,
+    contracts: HashMap<String, Symbol>,
+    price_limits: HashMap<String, PriceLimit>,
+    limits: HashMap<String, ContractLimits>,
+    positions: HashMap<(String, String), f64>,
+    in_flight: HashMap<ClientOrderId, InFlight>,
+    max_orders: Option<(usize, Duration)>,
+    sent: VecDeque<Instant>,
+}
+
+// Validates orders before `place_order` / `place_orders` touch the network: price within
+// the `contract_price_limit` band, price on `price_tick`, position and notional limits
+// per contract, and a max order rate. Clones share state, so the checker can be kept
+// up to date from another task while a client holds it.
+//
+// Position limits count held volume, the other orders in the same call, and accepted
+// opening orders still working on the book. Working orders are tracked by client order
+// id until `handle_event` sees them filled or cancelled, or `release` drops them.
+#[derive(Debug, Clone)]
+pub struct PreTradeChecker {
+    state: Arc<Mutex<CheckerState>>,
+}
+
+impl PreTradeChecker {
+    pub fn new(tick_policy: TickPolicy) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CheckerState {
+                tick_policy,
+                contracts: HashMap::new(),
+                price_limits: HashMap::new(),
+                limits: HashMap::new(),
+                positions: HashMap::new(),
+                in_flight: HashMap::new(),
+                max_orders: None,
+                sent: VecDeque::new(),
+            })),
+        }
+    }
+
+    // At most `max_orders` orders per `interval`.
+    pub fn set_max_order_rate(&self, max_orders: usize, interval: Duration) {
+        self.state.lock().unwrap().max_orders = Some((max_orders, interval));
+    }
+
+    // Limits by contract code, or by symbol for all of its contracts.
+    pub fn set_limits(&self, contract: &str, limits: ContractLimits) {
+        self.state.lock().unwrap().limits.insert(contract.to_string(), limits);
+    }
+
+    pub fn set_contracts(&self, contracts: &[Symbol]) {
+        let mut state = self.state.lock().unwrap();
+        for c in contracts {
+            state.contracts.insert(c.contract_code.to_string(), c.clone());
+        }
+    }
+
+    pub fn set_price_limits(&self, limits: &[PriceLimit]) {
+        let mut state = self.state.lock().unwrap();
+        for l in limits {
+            state.price_limits.insert(l.contract_code.to_string(), l.clone());
+        }
+    }
+
+    pub fn set_position(&self, contract_code: &str, direction: &str, volume: f64) {
+        self.state
+            .lock()
+            .unwrap()
+            .positions
+            .insert((contract_code.to_string(), direction.to_string()), volume);
+    }
+
+    pub fn set_positions(&self, positions: &[Position]) {
+        for p in positions {
+            self.set_position(p.contract_code.as_str(), &p.direction, p.volume);
+        }
+    }
+
+    // Stop counting an order that was rejected or never reached the exchange.
+    pub fn release(&self, client_order_id: ClientOrderId) {
+        self.state.lock().unwrap().in_flight.remove(&client_order_id);
+    }
+
+    pub fn handle_event(&self, event: &WebsocketEvent) {
+        match event {
+            WebsocketEvent::Position(msg) => self.set_positions(&msg.data),
+            WebsocketEvent::Order(msg) => self.apply_order(msg),
+            _ => (),
+        }
+    }
+
+    // Filled volume moves from the working order into the position push.
+    fn apply_order(&self, order: &OrderWSResponse) {
+        let id = match order.client_order_id {
+            Some(id) => id,
+            None => return,
+        };
+        let mut state = self.state.lock().unwrap();
+        if order.status.is_terminal() {
+            state.in_flight.remove(&id);
+        } else if let Some(pending) = state.in_flight.get_mut(&id) {
+            pending.volume = f64::from(order.volume.saturating_sub(order.trade_volume));
+        }
+    }
+
+    // Load contract metadata and price bands for a symbol.
+    pub async fn refresh(&self, client: &HuobiFuture, symbol: &str) -> Fallible<()> {
+        let contracts = client.get_contract_info(symbol.to_string(), None, None)?.await?;
+        self.set_contracts(&contracts.data.unwrap_or_default());
+
+        let limits = client.get_price_limit(symbol.to_string(), None, None)?.await?;
+        self.set_price_limits(&limits.data.unwrap_or_default());
+
+        Ok(())
+    }
+
+    // Check a group of orders, rounding prices in place under `TickPolicy::Round`.
+    // Nothing is modified, tracked or rate-limited unless every order passes.
+    pub fn check(&self, orders: &mut [OrderRequest]) -> Fallible<()> {
+        let mut state = self.state.lock().unwrap();
+
+        let mut batch: HashMap<(String, String), f64> = HashMap::new();
+        let mut checked = Vec::with_capacity(orders.len());
+        for order in orders.iter() {
+            let (price, key) = state.check_order(order, &batch)?;
+            if let Some(key) = &key {
+                *batch.entry(key.clone()).or_insert(0.0) += f64::from(order.volume);
+            }
+            checked.push((price, key));
+        }
+        state.check_rate(orders.len())?;
+
+        for (order, (price, key)) in orders.iter_mut().zip(checked) {
+            order.price = price;
+            if let (Some(key), Some(id)) = (key, order.client_order_id) {
+                let volume = f64::from(order.volume);
+                state.in_flight.insert(id, InFlight { key, volume });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CheckerState {
+    fn resolve(&self, order: &OrderRequest) -> Option<&Symbol> {
+        match &order.contract_code {
+            Some(code) => self.contracts.get(code.as_str()),
+            None => self.contracts.values().find(|c| {
+                Some(&c.symbol) == order.symbol.as_ref() && Some(&c.contract_type) == order.contract_type.as_ref()
+            }),
+        }
+    }
+
+    // Volume of accepted opening orders still working for a contract and direction.
+    fn working(&self, key: &(String, String)) -> f64 {
+        self.in_flight.values().filter(|o| &o.key == key).map(|o| o.volume).sum()
+    }
+
+    // Returns the checked price and, for opening orders, the position the order adds to.
+    fn check_order(
+        &self,
+        order: &OrderRequest,
+        batch: &HashMap<(String, String), f64>,
+    ) -> Fallible<(Option<f64>, Option<(String, String)>)> {
+        let label = order
+            .contract_code
+            .as_ref()
+            .map(ToString::to_string)
+            .or_else(|| order.symbol.as_ref().map(|s| format!("{} {}", s, order.contract_type.as_deref().unwrap_or(""))))
+            .unwrap_or_default();
+        let reject = |reason: String| -> failure::Error {
+            Error::PreTradeRejected { contract: label.clone(), reason }.into()
+        };
+
+        let contract = self
+            .resolve(order)
+            .ok_or_else(|| reject("contract metadata not loaded".to_string()))?;
+
+        if order.volume == 0 {
+            return Err(reject("volume must be positive".to_string()));
+        }
+
+        // opponent / optimal_* orders carry no price of their own
+        let priced = matches!(order.order_price_type.as_str(), "limit" | "post_only" | "fok" | "ioc");
+        let price = match order.price {
+            Some(price) if priced => Some(self.check_price(contract, &order.direction, price).map_err(reject)?),
+            price => price,
+        };
+
+        if order.offset != "open" {
+            return Ok((price, None));
+        }
+
+        let key = (contract.contract_code.to_string(), order.direction.clone());
+        let limits = self
+            .limits
+            .get(contract.contract_code.as_str())
+            .or_else(|| self.limits.get(&contract.symbol))
+            .copied()
+            .unwrap_or_default();
+        let held = self.positions.get(&key).copied().unwrap_or(0.0)
+            + self.working(&key)
+            + batch.get(&key).copied().unwrap_or(0.0);
+        let after = held + f64::from(order.volume);
+
+        if let Some(max) = limits.max_position {
+            if after > max {
+                return Err(reject(format!("position {} would exceed max {}", after, max)));
+            }
+        }
+        if let Some(max) = limits.max_notional {
+            let notional = after * contract.contract_size;
+            if notional > max {
+                return Err(reject(format!("notional {} USD would exceed max {}", notional, max)));
+            }
+        }
+
+        Ok((price, Some(key)))
+    }
+
+    fn check_price(&self, contract: &Symbol, direction: &str, price: f64) -> Result<f64, String> {
+        if price <= 0.0 {
+            return Err(format!("invalid price {}", price));
+        }
+
+        let tick = contract.price_tick;
+        let mut price = price;
+        if tick > 0.0 {
+            let ticks = price / tick;
+            if (ticks - ticks.round()).abs() > 1e-6 {
+                match self.tick_policy {
+                    TickPolicy::Reject => return Err(format!("price {} is not a multiple of tick {}", price, tick)),
+                    TickPolicy::Round => {
+                        let ticks = if direction == "buy" { ticks.floor() } else { ticks.ceil() };
+                        price = ticks * tick;
+                    }
+                }
+            }
+            // drop float noise from the multiplication
+            let decimals = (-tick.log10()).ceil().max(0.0) as i32;
+            let scale = 10f64.powi(decimals);
+            price = (price * scale).round() / scale;
+        }
+
+        if let Some(band) = self.price_limits.get(contract.contract_code.as_str()) {
+            if price > band.high_limit || price < band.low_limit {
+                return Err(format!(
+                    "price {} outside limit band [{}, {}]",
+                    price, band.low_limit, band.high_limit
+                ));
+            }
+        }
+
+        Ok(price)
+    }
+
+    fn check_rate(&mut self, count: usize) -> Fallible<()> {
+        let (max, interval) = match self.max_orders {
+            Some(rate) => rate,
+            None => return Ok(()),
+        };
+
+        let now = Instant::now();
+        while let Some(&t) = self.sent.front() {
+            if now.duration_since(t) >= interval {
+                self.sent.pop_front();
+            } else {
+                break;
+            }
+        }
+        if self.sent.len() + count > max {
+            return Err(Error::PreTradeRejected {
+                contract: "all contracts".to_string(),
+                reason: format!("order rate above {} per {:?}", max, interval),
+            }
+            .into());
+        }
+        for _ in 0..count {
+            self.sent.push_back(now);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn checker(max_position: f64) -> PreTradeChecker {
+        let checker = PreTradeChecker::new(TickPolicy::Reject);
+        let contract: Symbol = serde_json::from_value(json!({
+            "symbol": "BTC", "contract_code": "BTC200925", "contract_type": "quarter", "contract_size": 100.0,
+            "price_tick": 0.01, "delivery_date": "20200925", "create_date": "20200612", "contract_status": 1
+        }))
+        .unwrap();
+        checker.set_contracts(&[contract]);
+        checker.set_limits("BTC", ContractLimits { max_position: Some(max_position), max_notional: None });
+        checker
+    }
+
+    fn open(id: u64, volume: u32) -> OrderRequest {
+        OrderRequest {
+            contract_code: Some("BTC200925".into()),
+            symbol: None,
+            contract_type: None,
+            client_order_id: Some(ClientOrderId(id)),
+            price: Some(8000.0),
+            volume,
+            direction: "buy".into(),
+            offset: "open".into(),
+            lever_rate: 10,
+            order_price_type: "limit".into(),
+            tpsl: None,
+        }
+    }
+
+    fn update(id: u64, volume: u32, trade_volume: u32, status: u32) -> WebsocketEvent {
+        WebsocketEvent::Order(
+            serde_json::from_value(json!({
+                "op": "notify", "topic": "orders.btc", "uid": "1", "ts": 0, "symbol": "BTC",
+                "contract_code": "BTC200925", "contract_type": "quarter", "volume": volume, "price": 8000.0,
+                "order_price_type": "limit", "direction": "buy", "offset": "open", "status": status,
+                "lever_rate": 10, "order_id": 1, "order_id_str": "1", "client_order_id": id,
+                "order_source": "api", "order_type": 1, "created_at": 0, "trade_volume": trade_volume,
+                "trade_turnover": 0.0, "fee": 0.0, "trade_avg_price": 8000.0, "margin_frozen": 0.0,
+                "profit": 0.0, "liquidation_type": "0", "trade": []
+            }))
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn orders_in_one_call_add_up() {
+        let checker = checker(10.0);
+        assert!(checker.check(&mut [open(1, 6), open(2, 5)]).is_err());
+        // a rejected call tracks nothing
+        assert!(checker.check(&mut [open(3, 6), open(4, 4)]).is_ok());
+    }
+
+    #[test]
+    fn working_orders_count_until_done() {
+        let checker = checker(10.0);
+        checker.check(&mut [open(1, 8)]).unwrap();
+        assert!(checker.check(&mut [open(2, 3)]).is_err());
+
+        // 5 of 8 filled and now in the position
+        checker.handle_event(&update(1, 8, 5, 4));
+        checker.set_position("BTC200925", "buy", 5.0);
+        assert!(checker.check(&mut [open(3, 3)]).is_err());
+        assert!(checker.check(&mut [open(4, 2)]).is_ok());
+
+        checker.handle_event(&update(1, 8, 5, 5));
+        checker.release(ClientOrderId(4));
+        assert!(checker.check(&mut [open(5, 5)]).is_ok());
+    }
+}
@@ -0,0 +1,47 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+// Sliding window limiter: at most `max_requests` acquisitions per `interval`.
+// Clones share the window, so one limiter can be handed to several clients.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    max_requests: usize,
+    interval: Duration,
+    sent: Arc<Mutex<VecDeque<Instant>>>,
+}
+
+impl RateLimiter {
+    pub fn new(max_requests: usize, interval: Duration) -> Self {
+        Self {
+            max_requests: max_requests.max(1),
+            interval,
+            sent: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    // Wait until a request may be sent, then record it.
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut sent = self.sent.lock().unwrap();
+                let now = Instant::now();
+                while let Some(&t) = sent.front() {
+                    if now.duration_since(t) >= self.interval {
+                        sent.pop_front();
+                    } else {
+                        break;
+                    }
+                }
+                if sent.len() < self.max_requests {
+                    sent.push_back(now);
+                    return;
+                }
+                self.interval - now.duration_since(sent[0])
+            };
+            tokio::time::delay_for(wait).await;
+        }
+    }
+}
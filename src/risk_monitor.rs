@@ -0,0 +1,353 @@
+use crate::{error::Error, models::*};
+use chrono::{DateTime, Utc};
+use failure::Fallible;
+use futures::future::{BoxFuture, FutureExt};
+use std::{collections::HashMap, future::Future};
+use tracing::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RiskMetric {
+    // `risk_rate` as reported by the exchange, lower is riskier
+    RiskRate,
+    // |last price - liquidation price| / last price
+    LiquidationDistance,
+    // (margin_position + margin_frozen) / margin_balance
+    MarginUsage,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RiskLevel {
+    Normal,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RiskLimit {
+    pub warning: Option<f64>,
+    pub critical: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RiskThresholds {
+    pub risk_rate: RiskLimit,
+    pub liquidation_distance: RiskLimit,
+    pub margin_usage: RiskLimit,
+}
+
+#[derive(Debug, Clone)]
+pub struct RiskAlert {
+    pub symbol: String,
+    pub metric: RiskMetric,
+    pub level: RiskLevel,
+    pub value: f64,
+    pub threshold: Option<f64>,
+    pub ts: DateTime<Utc>,
+}
+
+// Evaluates account margin data from the `accounts.*` topic or `get_account_info`
+// against configured thresholds. Alerts fire when a metric changes level, so a
+// breached account does not alert on every push; critical alerts also run the
+// de-risking action, e.g. a `lightning_close` of the symbol's positions.
+//
+// A level is only recorded once its handlers succeed, so a failed alert or critical
+// action is retried on the next push that is still at that level.
+pub struct RiskMonitor {
+    thresholds: RiskThresholds,
+    last_prices: HashMap<String, f64>,
+    levels: HashMap<(String, RiskMetric), RiskLevel>,
+    on_alert: Box<dyn FnMut(&RiskAlert) -> Fallible<()> + Send>,
+    on_critical: Option<Box<dyn FnMut(&RiskAlert) -> BoxFuture<'static, Fallible<()>> + Send>>,
+}
+
+impl RiskMonitor {
+    pub fn new(thresholds: RiskThresholds) -> Self {
+        Self {
+            thresholds,
+            last_prices: HashMap::new(),
+            levels: HashMap::new(),
+            on_alert: Box::new(|alert| {
+                warn!("[RiskMonitor] {:?}", alert);
+                Ok(())
+            }),
+            on_critical: None,
+        }
+    }
+
+    #[must_use]
+    pub fn on_alert<Callback: 'static>(mut self, handler: Callback) -> Self
+    where
+        Callback: FnMut(&RiskAlert) -> Fallible<()> + Send,
+    {
+        self.on_alert = Box::new(handler);
+        self
+    }
+
+    // The action is awaited before the critical level is recorded.
+    #[must_use]
+    pub fn on_critical<Callback: 'static, Fut>(mut self, mut action: Callback) -> Self
+    where
+        Callback: FnMut(&RiskAlert) -> Fut + Send,
+        Fut: Future<Output = Fallible<()>> + Send + 'static,
+    {
+        self.on_critical = Some(Box::new(move |alert| action(alert).boxed()));
+        self
+    }
+
+    pub fn level(&self, symbol: &str, metric: RiskMetric) -> RiskLevel {
+        self.levels.get(&(symbol.to_string(), metric)).copied().unwrap_or(RiskLevel::Normal)
+    }
+
+    // Liquidation distance needs a reference price; positions pushes carry `last_price`.
+    pub fn set_last_price(&mut self, symbol: &str, price: f64) {
+        self.last_prices.insert(symbol.to_string(), price);
+    }
+
+    pub async fn evaluate(&mut self, account: &Account, ts: DateTime<Utc>) -> Fallible<()> {
+        self.evaluate_margin(
+            &account.symbol,
+            account.risk_rate,
+            account.liquidation_price,
+            account.margin_position + account.margin_frozen,
+            account.margin_balance,
+            ts,
+        )
+        .await
+    }
+
+    pub async fn evaluate_position_info(&mut self, info: &AccountPosition, ts: DateTime<Utc>) -> Fallible<()> {
+        for p in info.positions.iter().flatten() {
+            if p.last_price > 0.0 {
+                self.set_last_price(&p.symbol, p.last_price);
+            }
+        }
+        self.evaluate_margin(
+            &info.symbol,
+            info.risk_rate,
+            info.liquidation_price,
+            info.margin_position + info.margin_frozen,
+            info.margin_balance,
+            ts,
+        )
+        .await
+    }
+
+    pub async fn handle_event(&mut self, event: &WebsocketEvent) -> Fallible<()> {
+        match event {
+            WebsocketEvent::Account(msg) => {
+                let mut errors = Vec::new();
+                for account in &msg.data {
+                    errors.extend(self.evaluate(account, msg.ts).await.err());
+                }
+                return combine(errors);
+            }
+            WebsocketEvent::Position(msg) => {
+                for p in &msg.data {
+                    if p.last_price > 0.0 {
+                        self.set_last_price(&p.symbol, p.last_price);
+                    }
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    async fn evaluate_margin(
+        &mut self,
+        symbol: &str,
+        risk_rate: Option<f64>,
+        liquidation_price: Option<f64>,
+        margin_used: f64,
+        margin_balance: f64,
+        ts: DateTime<Utc>,
+    ) -> Fallible<()> {
+        let limits = self.thresholds.clone();
+        // a failing metric must not keep the others from being checked
+        let mut errors = Vec::new();
+
+        if let Some(rate) = risk_rate {
+            errors.extend(self.check(symbol, RiskMetric::RiskRate, rate, limits.risk_rate, true, ts).await.err());
+        }
+
+        let last_price = self.last_prices.get(symbol).copied();
+        if let (Some(liq), Some(last)) = (liquidation_price, last_price) {
+            if liq > 0.0 && last > 0.0 {
+                let distance = (last - liq).abs() / last;
+                let result = self
+                    .check(symbol, RiskMetric::LiquidationDistance, distance, limits.liquidation_distance, true, ts)
+                    .await;
+                errors.extend(result.err());
+            }
+        }
+
+        if margin_balance > 0.0 {
+            let usage = margin_used / margin_balance;
+            errors.extend(self.check(symbol, RiskMetric::MarginUsage, usage, limits.margin_usage, false, ts).await.err());
+        }
+
+        combine(errors)
+    }
+
+    async fn check(
+        &mut self,
+        symbol: &str,
+        metric: RiskMetric,
+        value: f64,
+        limit: RiskLimit,
+        lower_is_riskier: bool,
+        ts: DateTime<Utc>,
+    ) -> Fallible<()> {
+        let breached = |threshold: Option<f64>| match threshold {
+            Some(t) if lower_is_riskier => value <= t,
+            Some(t) => value >= t,
+            None => false,
+        };
+        let (level, threshold) = if breached(limit.critical) {
+            (RiskLevel::Critical, limit.critical)
+        } else if breached(limit.warning) {
+            (RiskLevel::Warning, limit.warning)
+        } else {
+            (RiskLevel::Normal, None)
+        };
+
+        if level == self.level(symbol, metric) {
+            return Ok(());
+        }
+
+        let alert = RiskAlert { symbol: symbol.to_string(), metric, level, value, threshold, ts };
+        let mut errors: Vec<_> = (self.on_alert)(&alert).err().into_iter().collect();
+        // de-risking never waits on the notification succeeding
+        if level == RiskLevel::Critical {
+            if let Some(action) = self.on_critical.as_mut() {
+                errors.extend(action(&alert).await.err());
+            }
+        }
+
+        if errors.is_empty() {
+            self.levels.insert((symbol.to_string(), metric), level);
+        }
+        combine(errors)
+    }
+}
+
+// One error as is, several as `Error::RiskHandlers`.
+fn combine(mut errors: Vec<failure::Error>) -> Fallible<()> {
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.remove(0)),
+        _ => Err(Error::RiskHandlers { reasons: errors.iter().map(ToString::to_string).collect() }.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    const LIMIT: RiskLimit = RiskLimit { warning: Some(1.0), critical: Some(0.5) };
+
+    #[test]
+    fn failed_critical_action_is_retried() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mut monitor = RiskMonitor::new(RiskThresholds::default()).on_critical(move |_| {
+            let attempt = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if attempt == 0 {
+                    Err(failure::err_msg("close failed"))
+                } else {
+                    Ok(())
+                }
+            }
+        });
+        let ts = Utc::now();
+
+        assert!(block_on(monitor.check("BTC", RiskMetric::RiskRate, 0.4, LIMIT, true, ts)).is_err());
+        assert_eq!(monitor.level("BTC", RiskMetric::RiskRate), RiskLevel::Normal);
+
+        block_on(monitor.check("BTC", RiskMetric::RiskRate, 0.4, LIMIT, true, ts)).unwrap();
+        assert_eq!(monitor.level("BTC", RiskMetric::RiskRate), RiskLevel::Critical);
+
+        // recorded, so no further runs while it stays critical
+        block_on(monitor.check("BTC", RiskMetric::RiskRate, 0.3, LIMIT, true, ts)).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn alerts_fire_on_level_changes() {
+        let alerts = Arc::new(AtomicUsize::new(0));
+        let counter = alerts.clone();
+        let mut monitor = RiskMonitor::new(RiskThresholds::default()).on_alert(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        });
+        let ts = Utc::now();
+
+        for value in &[2.0, 0.9, 0.8, 0.4, 2.0] {
+            block_on(monitor.check("BTC", RiskMetric::RiskRate, *value, LIMIT, true, ts)).unwrap();
+        }
+        assert_eq!(alerts.load(Ordering::SeqCst), 3);
+        assert_eq!(monitor.level("BTC", RiskMetric::RiskRate), RiskLevel::Normal);
+    }
+
+    #[test]
+    fn failing_alert_does_not_block_the_critical_action() {
+        let closes = Arc::new(AtomicUsize::new(0));
+        let counter = closes.clone();
+        let mut monitor = RiskMonitor::new(RiskThresholds::default())
+            .on_alert(|_| Err(failure::err_msg("webhook down")))
+            .on_critical(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { Ok(()) }
+            });
+
+        let err = block_on(monitor.check("BTC", RiskMetric::RiskRate, 0.4, LIMIT, true, Utc::now())).unwrap_err();
+        assert_eq!(err.to_string(), "webhook down");
+        assert_eq!(closes.load(Ordering::SeqCst), 1);
+        // not recorded, so the alert is retried
+        assert_eq!(monitor.level("BTC", RiskMetric::RiskRate), RiskLevel::Normal);
+    }
+
+    fn account(symbol: &str, risk_rate: f64) -> Account {
+        serde_json::from_value(serde_json::json!({
+            "symbol": symbol, "margin_balance": 1.0, "margin_static": 1.0, "margin_position": 0.9,
+            "margin_frozen": 0.0, "margin_available": 0.1, "profit_real": 0.0, "profit_unreal": 0.0,
+            "risk_rate": risk_rate, "liquidation_price": null, "withdraw_available": 0.1,
+            "lever_rate": 20.0, "adjust_factor": 0.1
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn one_failing_metric_does_not_hide_the_others() {
+        let thresholds = RiskThresholds {
+            risk_rate: LIMIT,
+            margin_usage: RiskLimit { warning: Some(0.8), critical: None },
+            ..Default::default()
+        };
+        let mut monitor = RiskMonitor::new(thresholds).on_alert(|alert| match alert.metric {
+            RiskMetric::RiskRate if alert.symbol == "BTC" => Err(failure::err_msg("risk rate alert failed")),
+            _ => Ok(()),
+        });
+
+        let push = WebsocketEvent::Account(WSAccountResponse {
+            op: "notify".into(),
+            topic: "accounts".into(),
+            ts: Utc::now(),
+            uid: None,
+            event: "snapshot".into(),
+            data: vec![account("BTC", 0.4), account("ETH", 0.4)],
+        });
+        assert!(block_on(monitor.handle_event(&push)).is_err());
+
+        assert_eq!(monitor.level("BTC", RiskMetric::RiskRate), RiskLevel::Normal);
+        assert_eq!(monitor.level("BTC", RiskMetric::MarginUsage), RiskLevel::Warning);
+        assert_eq!(monitor.level("ETH", RiskMetric::RiskRate), RiskLevel::Critical);
+        assert_eq!(monitor.level("ETH", RiskMetric::MarginUsage), RiskLevel::Warning);
+    }
+}
@@ -0,0 +1,296 @@
+use crate::error::Error;
+use data_encoding::BASE64;
+use failure::Fallible;
+use futures::future::{self, BoxFuture};
+use http::Method;
+use ring::{digest, hmac};
+use serde::Deserialize;
+use std::{
+    fmt,
+    path::PathBuf,
+    sync::Arc,
+};
+use zeroize::{Zeroize, Zeroizing};
+
+// API key pair. The secret is wiped from memory on drop and never printed.
+#[derive(Clone)]
+pub struct Credentials {
+    access_key: String,
+    secret_key: Zeroizing<String>,
+}
+
+impl Credentials {
+    pub fn new(access_key: &str, secret_key: &str) -> Self {
+        Self {
+            access_key: access_key.into(),
+            secret_key: Zeroizing::new(secret_key.into()),
+        }
+    }
+
+    pub fn access_key(&self) -> &str {
+        &self.access_key
+    }
+
+    pub fn secret_key(&self) -> &str {
+        &self.secret_key
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_key", &self.access_key)
+            .field("secret_key", &"<redacted>")
+            .finish()
+    }
+}
+
+// Source of the key pair, queried each time a request is signed so keys can rotate.
+pub trait CredentialProvider: Send + Sync {
+    fn credentials(&self) -> Fallible<Credentials>;
+}
+
+impl CredentialProvider for Credentials {
+    fn credentials(&self) -> Fallible<Credentials> {
+        Ok(self.clone())
+    }
+}
+
+// Reads the key pair from two environment variables.
+#[derive(Debug, Clone)]
+pub struct EnvCredentials {
+    access_key_var: String,
+    secret_key_var: String,
+}
+
+impl EnvCredentials {
+    pub fn new(access_key_var: &str, secret_key_var: &str) -> Self {
+        Self {
+            access_key_var: access_key_var.into(),
+            secret_key_var: secret_key_var.into(),
+        }
+    }
+}
+
+impl Default for EnvCredentials {
+    fn default() -> Self {
+        Self::new("HUOBI_ACCESS_KEY", "HUOBI_SECRET_KEY")
+    }
+}
+
+impl CredentialProvider for EnvCredentials {
+    fn credentials(&self) -> Fallible<Credentials> {
+        let access_key = std::env::var(&self.access_key_var).map_err(|_| Error::NoApiKeySet)?;
+        let secret_key = Zeroizing::new(std::env::var(&self.secret_key_var).map_err(|_| Error::NoApiKeySet)?);
+        Ok(Credentials::new(&access_key, &secret_key))
+    }
+}
+
+// Reads the key pair from a JSON file: `{"access_key": "...", "secret_key": "..."}`.
+#[derive(Debug, Clone)]
+pub struct FileCredentials {
+    path: PathBuf,
+}
+
+impl FileCredentials {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[derive(Deserialize)]
+struct CredentialsFile {
+    access_key: String,
+    secret_key: String,
+}
+
+impl Drop for CredentialsFile {
+    fn drop(&mut self) {
+        self.secret_key.zeroize();
+    }
+}
+
+impl CredentialProvider for FileCredentials {
+    fn credentials(&self) -> Fallible<Credentials> {
+        let content = Zeroizing::new(std::fs::read_to_string(&self.path)?);
+        let file: CredentialsFile = serde_json::from_str(&content)?;
+        Ok(Credentials::new(&file.access_key, &file.secret_key))
+    }
+}
+
+// Produces Huobi signature version 2 signatures.
+//
+// `sign_payload` is asynchronous so that the secret can live in a remote signing
+// service; the local `HmacSigner` resolves immediately.
+pub trait Signer: Send + Sync {
+    fn access_key(&self) -> Fallible<String>;
+
+    fn sign_payload(&self, payload: String) -> BoxFuture<'static, Fallible<String>>;
+}
+
+// Signs locally with HmacSHA256 using keys from a `CredentialProvider`.
+#[derive(Debug)]
+pub struct HmacSigner<P> {
+    provider: P,
+}
+
+impl<P: CredentialProvider> HmacSigner<P> {
+    pub fn new(provider: P) -> Self {
+        Self { provider }
+    }
+}
+
+impl<P: CredentialProvider> Signer for HmacSigner<P> {
+    fn access_key(&self) -> Fallible<String> {
+        Ok(self.provider.credentials()?.access_key().to_string())
+    }
+
+    fn sign_payload(&self, payload: String) -> BoxFuture<'static, Fallible<String>> {
+        let signature = self
+            .provider
+            .credentials()
+            .map(|c| sign_hmac_sha256_base64(c.secret_key(), &payload));
+        Box::pin(future::ready(signature))
+    }
+}
+
+pub fn hmac_signer(access_key: &str, secret_key: &str) -> Arc<dyn Signer> {
+    Arc::new(HmacSigner::new(Credentials::new(access_key, secret_key)))
+}
+
+// Builds the signed query for `method host path`, `Signature` last.
+//
+// The payload is `METHOD\nhost\npath\nsorted_query`, where the query holds the auth
+// parameters plus any business parameters that travel in the URL (GET/DELETE).
+pub async fn sign_request(
+    signer: &dyn Signer,
+    method: &Method,
+    host: &str,
+    path: &str,
+    params: Vec<(String, String)>,
+    timestamp: &str,
+) -> Fallible<Vec<(String, String)>> {
+    let mut query = params;
+    query.push(("AccessKeyId".to_string(), signer.access_key()?));
+    query.push(("SignatureMethod".to_string(), "HmacSHA256".to_string()));
+    query.push(("SignatureVersion".to_string(), "2".to_string()));
+    query.push(("Timestamp".to_string(), timestamp.to_string()));
+
+    query.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let payload = format!(
+        "{}\n{}\n{}\n{}",
+        method.as_str(),
+        host,
+        path,
+        build_query_string(&query)
+    );
+    let signature = signer.sign_payload(payload).await?;
+
+    query.push(("Signature".to_string(), signature));
+    Ok(query)
+}
+
+pub fn sign_hmac_sha256_base64(secret: &str, payload: &str) -> String {
+    let signed_key = hmac::SigningKey::new(&digest::SHA256, secret.as_bytes());
+    let signature = hmac::sign(&signed_key, payload.as_bytes());
+    BASE64.encode(signature.as_ref())
+}
+
+pub fn build_query_string(parameters: &[(String, String)]) -> String {
+    parameters
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, percent_encode(value)))
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+pub fn percent_encode(source: &str) -> String {
+    use percent_encoding::{define_encode_set, utf8_percent_encode, USERINFO_ENCODE_SET};
+    define_encode_set! {
+        pub CUSTOM_ENCODE_SET = [USERINFO_ENCODE_SET] | { '+', ',' }
+    }
+    utf8_percent_encode(source, CUSTOM_ENCODE_SET).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    const ACCESS_KEY: &str = "e2xxxxxx-99xxxxxx-84xxxxxx-7xxxx";
+    const SECRET_KEY: &str = "b0xxxxxx-c6xxxxxx-94xxxxxx-dxxxx";
+    const TIMESTAMP: &str = "2020-09-01T12:00:00";
+    const AUTH_QUERY: &str = "AccessKeyId=e2xxxxxx-99xxxxxx-84xxxxxx-7xxxx&SignatureMethod=HmacSHA256\
+                              &SignatureVersion=2&Timestamp=2020-09-01T12%3A00%3A00";
+
+    // keeps the last payload so the canonical string can be checked
+    struct Recording {
+        inner: HmacSigner<Credentials>,
+        payload: Mutex<Option<String>>,
+    }
+
+    impl Signer for Recording {
+        fn access_key(&self) -> Fallible<String> {
+            self.inner.access_key()
+        }
+
+        fn sign_payload(&self, payload: String) -> BoxFuture<'static, Fallible<String>> {
+            *self.payload.lock().unwrap() = Some(payload.clone());
+            self.inner.sign_payload(payload)
+        }
+    }
+
+    fn sign(method: Method, host: &str, path: &str, params: &[(&str, &str)]) -> (String, Vec<(String, String)>) {
+        let signer = Recording {
+            inner: HmacSigner::new(Credentials::new(ACCESS_KEY, SECRET_KEY)),
+            payload: Mutex::new(None),
+        };
+        let params = params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let query = futures::executor::block_on(sign_request(&signer, &method, host, path, params, TIMESTAMP)).unwrap();
+        let payload = signer.payload.lock().unwrap().take().unwrap();
+        (payload, query)
+    }
+
+    fn signature(query: &[(String, String)]) -> &str {
+        let (key, value) = query.last().unwrap();
+        assert_eq!(key, "Signature");
+        value
+    }
+
+    #[test]
+    fn get_on_futures_host() {
+        let (payload, query) = sign(
+            Method::GET,
+            "api.hbdm.vn",
+            "/api/v1/contract_order_limit",
+            &[("symbol", "BTC"), ("order_price_type", "limit")],
+        );
+        assert_eq!(
+            payload,
+            format!("GET\napi.hbdm.vn\n/api/v1/contract_order_limit\n{}&order_price_type=limit&symbol=BTC", AUTH_QUERY)
+        );
+        assert_eq!(signature(&query), "0bgvH628w3D8YYoRusy20qVa20GEKd02YMS1PY49SQ8=");
+        assert_eq!(query.len(), 7);
+    }
+
+    #[test]
+    fn post_on_futures_host() {
+        let (payload, query) = sign(Method::POST, "api.hbdm.vn", "/api/v1/contract_cancelall", &[]);
+        assert_eq!(payload, format!("POST\napi.hbdm.vn\n/api/v1/contract_cancelall\n{}", AUTH_QUERY));
+        assert_eq!(signature(&query), "wcuAuCjDIxf1DOvgL+PoYbN3sht3KtQEm5BYNhW5vhU=");
+    }
+
+    #[test]
+    fn post_on_spot_transfer_host() {
+        let (payload, query) = sign(Method::POST, "api.huobi.pro", "/v1/futures/transfer", &[]);
+        assert_eq!(payload, format!("POST\napi.huobi.pro\n/v1/futures/transfer\n{}", AUTH_QUERY));
+        assert_eq!(signature(&query), "i1mer75NGEU78eZ3vIP/Q6cqJksuglh75l/HGRWS2hc=");
+    }
+
+    #[test]
+    fn encodes_reserved_characters() {
+        assert_eq!(percent_encode("2020-09-01T12:00:00"), "2020-09-01T12%3A00%3A00");
+        assert_eq!(percent_encode("a+b,c"), "a%2Bb%2Cc");
+    }
+}
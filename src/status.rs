@@ -0,0 +1,108 @@
+use crate::models::{Heartbeat, ServiceHeartbeat, WebsocketEvent};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::watch;
+
+// Service name of the delivery futures this crate trades.
+pub const FUTURES_SERVICE: &str = "futures";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceStatus {
+    pub up: bool,
+    // milliseconds timestamp, announced while the service is down
+    pub estimated_recovery_time: Option<u64>,
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SystemStatus {
+    pub services: HashMap<String, ServiceStatus>,
+}
+
+impl SystemStatus {
+    // Services that have not reported yet are assumed to be up.
+    pub fn is_up(&self, service: &str) -> bool {
+        self.services.get(service).map_or(true, |s| s.up)
+    }
+
+    pub fn maintenance(&self, service: &str) -> Option<&ServiceStatus> {
+        self.services.get(service).filter(|s| !s.up)
+    }
+}
+
+// Tracks per-service heartbeat state from `/heartbeat/` polls and the
+// `public.$service.heartbeat` topic, published through a watch channel.
+#[derive(Clone)]
+pub struct StatusMonitor {
+    inner: Arc<Mutex<(SystemStatus, watch::Sender<SystemStatus>)>>,
+    rx: watch::Receiver<SystemStatus>,
+}
+
+impl Default for StatusMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatusMonitor {
+    pub fn new() -> Self {
+        let (tx, rx) = watch::channel(SystemStatus::default());
+        Self {
+            inner: Arc::new(Mutex::new((SystemStatus::default(), tx))),
+            rx,
+        }
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<SystemStatus> {
+        self.rx.clone()
+    }
+
+    pub fn status(&self) -> SystemStatus {
+        self.rx.borrow().clone()
+    }
+
+    pub fn update_service(&self, service: &str, heartbeat: u32, estimated_recovery_time: Option<u64>, ts: u64) {
+        let mut guard = self.inner.lock().unwrap();
+        let (status, tx) = &mut *guard;
+
+        let next = ServiceStatus {
+            up: heartbeat == 1,
+            estimated_recovery_time,
+            updated_at: ts,
+        };
+        if let Some(prev) = status.services.get(service) {
+            if prev.updated_at > ts || (prev.up == next.up && prev.estimated_recovery_time == next.estimated_recovery_time) {
+                return;
+            }
+        }
+
+        status.services.insert(service.to_string(), next);
+        let _ = tx.broadcast(status.clone());
+    }
+
+    pub fn apply_heartbeat(&self, heartbeat: &Heartbeat, ts: u64) {
+        self.update_service(FUTURES_SERVICE, heartbeat.heartbeat, heartbeat.estimated_recovery_time, ts);
+        if let Some(hb) = heartbeat.swap_heartbeat {
+            self.update_service("swap", hb, heartbeat.swap_estimated_recovery_time, ts);
+        }
+        if let Some(hb) = heartbeat.linear_swap_heartbeat {
+            self.update_service("linear-swap", hb, heartbeat.linear_swap_estimated_recovery_time, ts);
+        }
+    }
+
+    pub fn apply_service_heartbeat(&self, service: &str, heartbeat: &ServiceHeartbeat, ts: u64) {
+        self.update_service(service, heartbeat.heartbeat, heartbeat.estimated_recovery_time, ts);
+    }
+
+    // Feed websocket events from the handler; anything but heartbeats is ignored.
+    pub fn handle_event(&self, event: &WebsocketEvent) {
+        if let WebsocketEvent::ServiceHeartbeat(msg) = event {
+            // topic is `public.$service.heartbeat`
+            if let Some(service) = msg.topic.split('.').nth(1) {
+                self.apply_service_heartbeat(service, &msg.data, msg.ts.timestamp_millis() as u64);
+            }
+        }
+    }
+}
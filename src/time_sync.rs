@@ -0,0 +1,59 @@
+use chrono::{DateTime, Duration, Utc};
+use std::sync::{
+    atomic::{AtomicBool, AtomicI64, Ordering},
+    Arc,
+};
+
+// Shared estimate of the exchange clock, used for request and websocket signing.
+//
+// The offset is `server - local` in milliseconds, estimated from `/api/v1/timestamp`
+// assuming the server stamped its reply halfway through the round trip.
+#[derive(Clone, Debug, Default)]
+pub struct TimeSync {
+    state: Arc<TimeSyncState>,
+}
+
+#[derive(Debug, Default)]
+struct TimeSyncState {
+    offset_ms: AtomicI64,
+    round_trip_ms: AtomicI64,
+    synced: AtomicBool,
+}
+
+impl TimeSync {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Record one measurement: local send time, server timestamp and local receive time.
+    pub fn update(&self, sent: DateTime<Utc>, server_ts_ms: i64, received: DateTime<Utc>) {
+        let round_trip = (received - sent).num_milliseconds().max(0);
+        let midpoint = sent.timestamp_millis() + round_trip / 2;
+
+        self.state.offset_ms.store(server_ts_ms - midpoint, Ordering::Relaxed);
+        self.state.round_trip_ms.store(round_trip, Ordering::Relaxed);
+        self.state.synced.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.state.synced.load(Ordering::Relaxed)
+    }
+
+    pub fn offset(&self) -> Duration {
+        Duration::milliseconds(self.state.offset_ms.load(Ordering::Relaxed))
+    }
+
+    pub fn round_trip(&self) -> Duration {
+        Duration::milliseconds(self.state.round_trip_ms.load(Ordering::Relaxed))
+    }
+
+    // Current time on the exchange clock.
+    pub fn now(&self) -> DateTime<Utc> {
+        Utc::now() + self.offset()
+    }
+
+    // Timestamp parameter in the format expected by signature version 2.
+    pub fn timestamp(&self) -> String {
+        self.now().format("%Y-%m-%dT%H:%M:%S").to_string()
+    }
+}
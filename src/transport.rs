@@ -1,12 +1,13 @@
 use crate::error::{HuobiResponse, Error};
+use crate::signer::{hmac_signer, sign_request, Signer};
+use crate::time_sync::TimeSync;
 use failure::Fallible;
 use futures::prelude::*;
-use ring::{digest, hmac};
 use http::Method;
 use reqwest_ext::*;
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::{to_string, to_value, Value};
-use data_encoding::BASE64;
+use std::sync::Arc;
 use tracing::*;
 use url::Url;
 
@@ -15,10 +16,34 @@ const SPOT_BASE: &str = "https://api.huobi.pro";
 static API_HOST: &str = "api.hbdm.vn";
 static SPOT_API_HOST: &str = "api.huobi.pro";
 
+// Product line an endpoint belongs to, selecting the REST host.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Product {
+    Future,
+    Spot,
+}
+
+impl Product {
+    pub fn base(self) -> &'static str {
+        match self {
+            Self::Future => BASE,
+            Self::Spot => SPOT_BASE,
+        }
+    }
+
+    pub fn host(self) -> &'static str {
+        match self {
+            Self::Future => API_HOST,
+            Self::Spot => SPOT_API_HOST,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Transport {
-    credential: Option<(String, String)>,
+    signer: Option<Arc<dyn Signer>>,
     client: reqwest::Client,
+    time_sync: TimeSync,
 }
 
 impl Default for Transport {
@@ -30,18 +55,32 @@ impl Default for Transport {
 impl Transport {
     pub fn new() -> Self {
         Self {
-            credential: None,
-            client: reqwest::Client::builder().build().unwrap()
+            signer: None,
+            client: reqwest::Client::builder().build().unwrap(),
+            time_sync: TimeSync::new(),
         }
     }
 
     pub fn with_credential(api_key: &str, api_secret: &str) -> Self {
+        Self::with_signer(hmac_signer(api_key, api_secret))
+    }
+
+    pub fn with_signer(signer: Arc<dyn Signer>) -> Self {
         Self {
             client: reqwest::Client::builder().build().unwrap(),
-            credential: Some((api_key.into(), api_secret.into())),
+            signer: Some(signer),
+            time_sync: TimeSync::new(),
         }
     }
 
+    pub fn time_sync(&self) -> TimeSync {
+        self.time_sync.clone()
+    }
+
+    pub fn signer(&self) -> Option<Arc<dyn Signer>> {
+        self.signer.clone()
+    }
+
     pub fn get<O, Q>(
         &self,
         endpoint: &str,
@@ -99,7 +138,7 @@ impl Transport {
         O: DeserializeOwned,
         Q: Serialize,
     {
-        self.signed_request::<_, _, ()>(Method::GET, endpoint, params, None)
+        self.signed_request::<_, _, ()>(Product::Future, Method::GET, endpoint, params, None)
     }
 
     pub fn signed_post<O, D>(
@@ -111,7 +150,19 @@ impl Transport {
         O: DeserializeOwned,
         D: Serialize,
     {
-        self.signed_request::<_, (), _>(Method::POST, endpoint, None, data)
+        self.signed_request::<_, (), _>(Product::Future, Method::POST, endpoint, None, data)
+    }
+
+    pub fn signed_spot_post<O, D>(
+        &self,
+        endpoint: &str,
+        data: Option<D>,
+    ) -> Fallible<impl Future<Output = Fallible<O>>>
+    where
+        O: DeserializeOwned,
+        D: Serialize,
+    {
+        self.signed_request::<_, (), _>(Product::Spot, Method::POST, endpoint, None, data)
     }
 
     pub fn signed_put<O, Q>(
@@ -123,7 +174,7 @@ impl Transport {
         O: DeserializeOwned,
         Q: Serialize,
     {
-        self.signed_request::<_, _, ()>(Method::PUT, endpoint, params, None)
+        self.signed_request::<_, _, ()>(Product::Future, Method::PUT, endpoint, params, None)
     }
 
     pub fn signed_delete<O, Q>(
@@ -135,7 +186,7 @@ impl Transport {
         O: DeserializeOwned,
         Q: Serialize,
     {
-        self.signed_request::<_, _, ()>(Method::DELETE, endpoint, params, None)
+        self.signed_request::<_, _, ()>(Product::Future, Method::DELETE, endpoint, params, None)
     }
 
     pub fn request<O, Q, D>(
@@ -181,6 +232,7 @@ impl Transport {
 
     pub fn signed_request<O, Q, D>(
         &self,
+        product: Product,
         method: Method,
         endpoint: &str,
         params: Option<Q>,
@@ -191,41 +243,33 @@ impl Transport {
         Q: Serialize,
         D: Serialize,
     {
-        let (key, secret) = self.check_key()?;
-        let mut query = params.map_or_else(Vec::new, |q| q.to_url_query());
-        query.push(("AccessKeyId".to_string(), key.to_string()));
-        query.push(("SignatureMethod".to_string(), "HmacSHA256".to_string()));
-        query.push(("SignatureVersion".to_string(), "2".to_string()));
-        let utctime = get_timestamp();
-        query.push(("Timestamp".to_string(), utctime));
-
-        query.sort_by(|a,b| a.0.as_str().cmp(b.0.as_str()));
+        let signer = self.check_key()?;
+        let client = self.client.clone();
+        let endpoint = endpoint.to_string();
+        let timestamp = self.time_sync.timestamp();
 
-        let paramss = build_query_string(&query);
+        let params = params.map_or_else(Vec::new, |q| q.to_url_query());
+        let body = data.map(|d| to_value(d)).transpose()?;
 
-        let api_host = if endpoint == "/v1/futures/transfer" { SPOT_API_HOST } else { API_HOST };
-        let api_url = if endpoint == "/v1/futures/transfer" { SPOT_BASE } else { BASE };
+        Ok(async move {
+            let query = sign_request(&*signer, &method, product.host(), &endpoint, params, &timestamp).await?;
 
-        let signature = sign_hmac_sha256_base64(
-            secret,
-            &format!("{}\n{}\n{}\n{}", "POST", api_host, endpoint, paramss,),
-        )?;
+            trace!("Signed {} {}{}", method, product.host(), endpoint);
 
-        trace!("Sign message: {}", signature);
+            let url = format!("{}{}", product.base(), endpoint);
+            let url = Url::parse_with_params(&url, &query)?;
 
-        let url = format!("{}{}", api_url, endpoint);
-        let mut url = Url::parse_with_params(&url, &query)?;
-        url.query_pairs_mut()
-            .append_pair("Signature", &signature);
+            let req = client
+                .request(method, url.as_str())
+                .typed_header(headers::UserAgent::from_static("alphaquant"))
+                .typed_header(headers::ContentType::json());
 
-        let req = self
-            .client
-            .request(method, url.as_str())
-            .typed_header(headers::UserAgent::from_static("alphaquant"))
-            .typed_header(headers::ContentType::json())
-            .json(&data);
+            // GET and DELETE carry everything in the signed query
+            let req = match body {
+                Some(body) => req.json(&body),
+                None => req,
+            };
 
-        Ok(async move {
             Ok(req
                 .send()
                 .await?
@@ -235,47 +279,12 @@ impl Transport {
         })
     }
 
-    fn check_key(&self) -> Fallible<(&str, &str)> {
-        match self.credential.as_ref() {
+    fn check_key(&self) -> Fallible<Arc<dyn Signer>> {
+        match self.signer.as_ref() {
             None => Err(Error::NoApiKeySet.into()),
-            Some((k, s)) => Ok((k, s)),
+            Some(signer) => Ok(signer.clone()),
         }
     }
-
-
-
-}
-
-
-pub fn sign_hmac_sha256_base64(secret: &str, digest: &str) -> Fallible<String> {
-
-    let signed_key = hmac::SigningKey::new(&digest::SHA256, secret.as_bytes());
-    let signature = hmac::sign(&signed_key, digest.as_bytes());
-    let b64_encoded_sig = BASE64.encode(signature.as_ref());
-
-    Ok(b64_encoded_sig)
-}
-
-pub fn build_query_string(parameters: &[(String,String)]) -> String 
-{
-    parameters
-        .iter()
-        .map(|(key, value)| format!("{}={}", key, percent_encode(&value.clone())))
-        .collect::<Vec<String>>()
-        .join("&")
-}
-
-pub fn percent_encode(source: &str) -> String {
-    use percent_encoding::{define_encode_set, utf8_percent_encode, USERINFO_ENCODE_SET};
-    define_encode_set! {
-        pub CUSTOM_ENCODE_SET = [USERINFO_ENCODE_SET] | { '+', ',' }
-    }
-    utf8_percent_encode(source, CUSTOM_ENCODE_SET).to_string()
-}
-
-pub fn get_timestamp() -> String {
-    let utc_time = chrono::Utc::now();
-    utc_time.format("%Y-%m-%dT%H:%M:%S").to_string()
 }
 
 trait ToUrlQuery: Serialize {